@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use postcard_telemetry::transport::{decode, Package};
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = data.to_vec();
+    let _ = decode::<Package>(&mut buf);
+});
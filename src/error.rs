@@ -3,12 +3,27 @@
 /// The error type returned by this library.
 #[derive(Debug)]
 pub enum Error {
-    /// The buffer provided to a function was too small.
+    /// The buffer provided to encode into was too small.
     BufferTooSmall,
-    /// The decoded data was invalid.
+    /// The decoded data was malformed.
     InvalidData,
     /// An internal data structure was saturated.
     Saturated,
+    /// A floating point value was NaN or infinite.
+    NonFinite,
+    /// A value did not fit into the target type without loss.
+    Overflow,
+    /// A value was outside of its valid range, such as the range
+    /// registered for a telemetry key, or the domain of an argument.
+    OutOfRange,
+    /// The decoded data ended before the item was complete.
+    Truncated,
+    /// The buffer provided to decode into was too small.
+    DecodeBufferTooSmall,
+    /// The frame to decode was longer than the maximum frame length.
+    FrameTooLong,
+    /// A telemetry key was not registered.
+    UnknownKey,
 }
 
 impl core::fmt::Display for Error {
@@ -17,6 +32,13 @@ impl core::fmt::Display for Error {
             Self::BufferTooSmall => write!(f, "buffer too small"),
             Self::InvalidData => write!(f, "invalid data"),
             Self::Saturated => write!(f, "saturated"),
+            Self::NonFinite => write!(f, "non-finite value"),
+            Self::Overflow => write!(f, "overflow"),
+            Self::OutOfRange => write!(f, "out of range"),
+            Self::Truncated => write!(f, "truncated data"),
+            Self::DecodeBufferTooSmall => write!(f, "decode buffer too small"),
+            Self::FrameTooLong => write!(f, "frame too long"),
+            Self::UnknownKey => write!(f, "unknown key"),
         }
     }
 }
@@ -0,0 +1,749 @@
+//! Signal filters
+//!
+//! Filters operate in place on slices of samples, so they can be used
+//! on fixed-size buffers without allocating. They are generic over
+//! [`num_traits::Float`] and work with both `f32` and `f64`.
+
+use num_traits::Float;
+
+/// Remove the DC component of a window of samples by subtracting the
+/// arithmetic mean of the slice from every element.
+pub fn remove_mean<T: Float>(seq: &mut [T]) {
+    if seq.is_empty() {
+        return;
+    }
+    let sum = seq.iter().fold(T::zero(), |acc, &x| acc + x);
+    let mean = sum / T::from(seq.len()).unwrap();
+    for x in seq.iter_mut() {
+        *x = *x - mean;
+    }
+}
+
+/// Apply a single-pole low-pass filter to a sequence in place.
+///
+/// `dt` is the sample period and `rc` the filter time constant, in
+/// the same unit. The cutoff frequency is `1 / (2π rc)`.
+pub fn low_pass<T: Float>(seq: &mut [T], dt: T, rc: T) {
+    let alpha = dt / (rc + dt);
+    let mut previous = match seq.first() {
+        Some(&x) => x,
+        None => return,
+    };
+    for x in seq.iter_mut().skip(1) {
+        previous = previous + alpha * (*x - previous);
+        *x = previous;
+    }
+}
+
+/// The group delay of [`low_pass`] at low frequencies, in samples.
+///
+/// This is how far the output of the filter trails a slowly varying
+/// input, which for a single pole is `rc / dt`.
+pub fn group_delay<T: Float>(dt: T, rc: T) -> T {
+    rc / dt
+}
+
+/// Shift a sequence filtered with [`low_pass`] back by its group
+/// delay, rounded to whole samples, to line it up with the raw
+/// signal. The end of the sequence, for which there is no aligned
+/// output yet, repeats the last sample.
+pub fn align<T: Float>(seq: &mut [T], dt: T, rc: T) {
+    let delay = group_delay(dt, rc).round().to_usize().unwrap_or(0);
+    let Some(&last) = seq.last() else {
+        return;
+    };
+    let delay = delay.min(seq.len());
+    seq.copy_within(delay.., 0);
+    let len = seq.len();
+    for x in seq[len - delay..].iter_mut() {
+        *x = last;
+    }
+}
+
+/// Apply a single-pole low-pass filter to a sequence of vectors in
+/// place, filtering each of the `D` components independently. See
+/// [`low_pass`].
+pub fn low_pass_vec<T: Float, const D: usize>(seq: &mut [[T; D]], dt: T, rc: T) {
+    let alpha = dt / (rc + dt);
+    let mut previous = match seq.first() {
+        Some(&x) => x,
+        None => return,
+    };
+    for x in seq.iter_mut().skip(1) {
+        for (p, x) in previous.iter_mut().zip(x.iter_mut()) {
+            *p = *p + alpha * (*x - *p);
+            *x = *p;
+        }
+    }
+}
+
+/// Apply a causal FIR filter with the given taps to a sequence in
+/// place.
+///
+/// Each output is `y[n] = taps[0] x[n] + taps[1] x[n - 1] + ...`,
+/// with samples before the start of the sequence taken as zero. The
+/// sequence is processed back to front, so no scratch space is needed.
+pub fn fir<T: Float>(seq: &mut [T], taps: &[T]) {
+    for n in (0..seq.len()).rev() {
+        let history = seq[..=n].iter().rev();
+        seq[n] = taps
+            .iter()
+            .zip(history)
+            .fold(T::zero(), |acc, (&tap, &x)| acc + tap * x);
+    }
+}
+
+/// Low-pass filter a sequence, then keep every `factor`-th sample.
+///
+/// Decimating by `factor` lowers the Nyquist frequency to
+/// `1 / (2 factor dt)`, and anything above it aliases into the
+/// output. The low-pass cutoff `1 / (2π rc)` should be well below that,
+/// which means `rc` should be at least `factor dt / π`, and preferably
+/// a few times more since a single pole rolls off slowly.
+///
+/// A `factor` of zero is treated as one.
+#[cfg(feature = "std")]
+pub fn decimate_lowpass<T: Float>(seq: &[T], factor: usize, dt: T, rc: T) -> Vec<T> {
+    let mut filtered = seq.to_vec();
+    low_pass(&mut filtered, dt, rc);
+    filtered.into_iter().step_by(factor.max(1)).collect()
+}
+
+/// Decimate a sequence for plotting while preserving extremes.
+///
+/// The sequence is split into at most `buckets` chunks of equal
+/// length (the last one may be shorter), and each chunk is reduced to
+/// its minimum and maximum, in the order they occur. Unlike averaging,
+/// this keeps short transients visible. The output holds two samples
+/// per non-empty bucket.
+#[cfg(feature = "std")]
+pub fn minmax_decimate(seq: &[f32], buckets: usize) -> Vec<f32> {
+    if seq.is_empty() || buckets == 0 {
+        return Vec::new();
+    }
+    let bucket_len = seq.len().div_ceil(buckets);
+    let mut decimated = Vec::with_capacity(2 * buckets.min(seq.len()));
+    for bucket in seq.chunks(bucket_len) {
+        let (mut min, mut max) = (0, 0);
+        for (i, &x) in bucket.iter().enumerate() {
+            if x < bucket[min] {
+                min = i;
+            }
+            if x > bucket[max] {
+                max = i;
+            }
+        }
+        decimated.push(bucket[min.min(max)]);
+        decimated.push(bucket[min.max(max)]);
+    }
+    decimated
+}
+
+/// Estimate the dominant frequency of a sequence by counting zero
+/// crossings, a cheap alternative to a Fourier transform. `dt` is the
+/// sample period, and the result is in cycles per unit of `dt`.
+///
+/// The mean is removed first, and a crossing only counts once the
+/// signal moves past a hysteresis band of a tenth of its peak
+/// deviation, so that noise around zero is not counted. The frequency
+/// is measured between the first and last crossing, and is zero if
+/// there are fewer than two.
+pub fn zero_crossing_frequency<T: Float>(seq: &[T], dt: T) -> T {
+    if seq.is_empty() {
+        return T::zero();
+    }
+    let mean = seq.iter().fold(T::zero(), |acc, &x| acc + x) / T::from(seq.len()).unwrap();
+    let peak = seq
+        .iter()
+        .fold(T::zero(), |acc, &x| acc.max((x - mean).abs()));
+    let hysteresis = peak / T::from(10).unwrap();
+
+    let mut positive = None;
+    let mut crossings = 0usize;
+    let (mut first, mut last) = (0, 0);
+    for (i, &x) in seq.iter().enumerate() {
+        let x = x - mean;
+        let state = if x > hysteresis {
+            true
+        } else if x < -hysteresis {
+            false
+        } else {
+            continue;
+        };
+        match positive {
+            Some(previous) if previous != state => {
+                if crossings == 0 {
+                    first = i;
+                }
+                last = i;
+                crossings += 1;
+            }
+            _ => {}
+        }
+        positive = Some(state);
+    }
+    if crossings < 2 {
+        return T::zero();
+    }
+    let half_periods = T::from(crossings - 1).unwrap();
+    half_periods / (T::from(2 * (last - first)).unwrap() * dt)
+}
+
+/// A second-order IIR filter section.
+///
+/// Coefficients are normalized so that `a0` is one, and the filter is
+/// evaluated in transposed direct form II. The constructors use the
+/// formulas from Robert Bristow-Johnson's Audio EQ Cookbook.
+///
+/// ```
+/// # use postcard_telemetry::filter::Biquad;
+/// let mut filter = Biquad::lowpass(50.0, 0.707, 1000.0);
+/// let smoothed = filter.process(1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Biquad<T> {
+    pub b0: T,
+    pub b1: T,
+    pub b2: T,
+    pub a1: T,
+    pub a2: T,
+    z1: T,
+    z2: T,
+}
+
+impl<T: Float> Biquad<T> {
+    /// Create a filter from normalized coefficients.
+    pub fn new(b0: T, b1: T, b2: T, a1: T, a2: T) -> Self {
+        Self {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            z1: T::zero(),
+            z2: T::zero(),
+        }
+    }
+
+    /// A second-order low-pass filter with the given cutoff frequency
+    /// and quality factor. A `q` of `1/√2` gives a Butterworth
+    /// response.
+    pub fn lowpass(cutoff_hz: T, q: T, sample_hz: T) -> Self {
+        let (cos, alpha) = Self::prewarp(cutoff_hz, q, sample_hz);
+        let one = T::one();
+        let two = one + one;
+        let b1 = one - cos;
+        Self::normalized(b1 / two, b1, b1 / two, one + alpha, -two * cos, one - alpha)
+    }
+
+    /// A second-order high-pass filter with the given cutoff frequency
+    /// and quality factor.
+    pub fn highpass(cutoff_hz: T, q: T, sample_hz: T) -> Self {
+        let (cos, alpha) = Self::prewarp(cutoff_hz, q, sample_hz);
+        let one = T::one();
+        let two = one + one;
+        let b0 = (one + cos) / two;
+        Self::normalized(b0, -(one + cos), b0, one + alpha, -two * cos, one - alpha)
+    }
+
+    /// A notch filter removing the given center frequency. Higher `q`
+    /// gives a narrower notch.
+    pub fn notch(cutoff_hz: T, q: T, sample_hz: T) -> Self {
+        let (cos, alpha) = Self::prewarp(cutoff_hz, q, sample_hz);
+        let one = T::one();
+        let two = one + one;
+        Self::normalized(one, -two * cos, one, one + alpha, -two * cos, one - alpha)
+    }
+
+    /// Filter a single sample.
+    pub fn process(&mut self, x: T) -> T {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    /// Filter a sequence in place, continuing from the current state.
+    pub fn process_slice(&mut self, seq: &mut [T]) {
+        for x in seq.iter_mut() {
+            *x = self.process(*x);
+        }
+    }
+
+    /// Clear the filter state, keeping the coefficients.
+    pub fn reset(&mut self) {
+        self.z1 = T::zero();
+        self.z2 = T::zero();
+    }
+
+    /// `cos(w0)` and `alpha` for the cookbook formulas.
+    fn prewarp(cutoff_hz: T, q: T, sample_hz: T) -> (T, T) {
+        let w0 = T::from(core::f64::consts::TAU).unwrap() * cutoff_hz / sample_hz;
+        let two = T::one() + T::one();
+        (w0.cos(), w0.sin() / (two * q))
+    }
+
+    fn normalized(b0: T, b1: T, b2: T, a0: T, a1: T, a2: T) -> Self {
+        Self::new(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
+    }
+}
+
+/// Remove a single interfering frequency from a sequence in place,
+/// using a [`Biquad::notch`] filter starting from rest.
+///
+/// Frequencies are in the same unit, typically Hz. A higher `q` gives
+/// a narrower notch but takes longer to settle.
+pub fn notch_filter<T: Float>(seq: &mut [T], cutoff_hz: T, q: T, sample_hz: T) {
+    Biquad::notch(cutoff_hz, q, sample_hz).process_slice(seq);
+}
+
+/// A filter that processes one sample at a time, keeping its own
+/// state between samples.
+///
+/// Closures from a sample to a sample are streaming filters too, which
+/// is handy for stateless stages like a dead band.
+pub trait StreamingFilter<T> {
+    /// Filter a single sample.
+    fn update(&mut self, x: T) -> T;
+}
+
+impl<T: Float> StreamingFilter<T> for Biquad<T> {
+    fn update(&mut self, x: T) -> T {
+        self.process(x)
+    }
+}
+
+impl<T, F: FnMut(T) -> T> StreamingFilter<T> for F {
+    fn update(&mut self, x: T) -> T {
+        self(x)
+    }
+}
+
+/// A sequence of streaming filter stages applied in order.
+///
+/// ```
+/// # use postcard_telemetry::filter::{Biquad, FilterPipeline, StreamingFilter};
+/// let mut pipeline = FilterPipeline::new()
+///     .stage(Biquad::highpass(0.5, 0.707, 100.0))
+///     .stage(|x: f32| if x.abs() < 0.01 { 0.0 } else { x });
+/// let filtered = pipeline.update(1.0);
+/// ```
+#[cfg(feature = "std")]
+pub struct FilterPipeline<T> {
+    stages: Vec<Box<dyn StreamingFilter<T>>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> FilterPipeline<T> {
+    /// Create an empty pipeline, which passes samples through.
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Append a stage.
+    pub fn stage(mut self, stage: impl StreamingFilter<T> + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Default for FilterPipeline<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> StreamingFilter<T> for FilterPipeline<T> {
+    fn update(&mut self, x: T) -> T {
+        self.stages.iter_mut().fold(x, |x, stage| stage.update(x))
+    }
+}
+
+/// An adaptive least-mean-squares filter with `N` taps, for removing
+/// noise that is correlated with a reference signal.
+///
+/// The filter learns to predict the noise in the input from the last
+/// `N` reference samples and subtracts its prediction. The step size
+/// `mu` trades adaptation speed for stability; it must be below
+/// `1 / (N * power)`, where `power` is the mean square of the
+/// reference.
+#[derive(Debug, Clone)]
+pub struct LmsFilter<T, const N: usize> {
+    mu: T,
+    weights: [T; N],
+    history: [T; N],
+}
+
+impl<T: Float, const N: usize> LmsFilter<T, N> {
+    /// Create a filter with step size `mu` and all weights zero.
+    pub fn new(mu: T) -> Self {
+        Self {
+            mu,
+            weights: [T::zero(); N],
+            history: [T::zero(); N],
+        }
+    }
+
+    /// Process one input sample along with the simultaneous reference
+    /// sample, returning the input with the predicted noise removed.
+    pub fn update(&mut self, input: T, reference: T) -> T {
+        if N == 0 {
+            return input;
+        }
+        self.history.copy_within(..N - 1, 1);
+        self.history[0] = reference;
+        let estimate = self
+            .weights
+            .iter()
+            .zip(self.history.iter())
+            .fold(T::zero(), |acc, (&w, &x)| acc + w * x);
+        let cleaned = input - estimate;
+        for (w, &x) in self.weights.iter_mut().zip(self.history.iter()) {
+            *w = *w + self.mu * cleaned * x;
+        }
+        cleaned
+    }
+
+    /// Forget the learned weights and the reference history.
+    pub fn reset(&mut self) {
+        self.weights = [T::zero(); N];
+        self.history = [T::zero(); N];
+    }
+}
+
+/// A window function for spectral analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    /// No windowing, all coefficients are one.
+    Rectangular,
+    /// Raised cosine, reaching zero at both ends.
+    Hann,
+    /// Raised cosine with non-zero ends, for better suppression of
+    /// the nearest side lobe.
+    Hamming,
+    /// Three-term cosine window with low side lobes at the cost of a
+    /// wider main lobe.
+    Blackman,
+}
+
+impl WindowKind {
+    /// The window coefficient for sample `n` of a window of length
+    /// `len`.
+    fn coefficient<T: Float>(self, n: usize, len: usize) -> T {
+        if len < 2 {
+            return T::one();
+        }
+        let c = |x: f64| T::from(x).unwrap();
+        let phase = c(core::f64::consts::TAU) * c(n as f64) / c((len - 1) as f64);
+        match self {
+            WindowKind::Rectangular => T::one(),
+            WindowKind::Hann => c(0.5) - c(0.5) * phase.cos(),
+            WindowKind::Hamming => c(0.54) - c(0.46) * phase.cos(),
+            WindowKind::Blackman => {
+                c(0.42) - c(0.5) * phase.cos() + c(0.08) * (phase + phase).cos()
+            }
+        }
+    }
+}
+
+/// Apply a symmetric window function to a sequence in place, to
+/// reduce spectral leakage before a Fourier transform.
+pub fn apply_window<T: Float>(seq: &mut [T], window: WindowKind) {
+    let len = seq.len();
+    for (n, x) in seq.iter_mut().enumerate() {
+        *x = *x * window.coefficient(n, len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_mean() {
+        let mut seq = [3.0f32, 5.0, 4.0, 8.0];
+        remove_mean(&mut seq);
+        assert_eq!(seq, [-2.0, 0.0, -1.0, 3.0]);
+        assert!(seq.iter().sum::<f32>().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_remove_mean_empty() {
+        let mut seq: [f64; 0] = [];
+        remove_mean(&mut seq);
+    }
+
+    #[test]
+    fn test_hann_window() {
+        let mut seq = [1.0f32; 9];
+        apply_window(&mut seq, WindowKind::Hann);
+        assert!(seq[0].abs() < 1e-6);
+        assert!(seq[8].abs() < 1e-6);
+        assert!((seq[4] - 1.0).abs() < 1e-6);
+        assert!((seq[2] - 0.5).abs() < 1e-6);
+        for n in 0..4 {
+            assert!(seq[n] < seq[n + 1]);
+            assert!((seq[n] - seq[8 - n]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_windows() {
+        let mut rectangular = [2.0f64; 5];
+        apply_window(&mut rectangular, WindowKind::Rectangular);
+        assert_eq!(rectangular, [2.0; 5]);
+
+        let mut hamming = [1.0f64; 5];
+        apply_window(&mut hamming, WindowKind::Hamming);
+        assert!((hamming[0] - 0.08).abs() < 1e-9);
+        assert!((hamming[2] - 1.0).abs() < 1e-9);
+
+        let mut blackman = [1.0f64; 5];
+        apply_window(&mut blackman, WindowKind::Blackman);
+        assert!(blackman[0].abs() < 1e-9);
+        assert!((blackman[2] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_low_pass() {
+        let mut seq = [0.0f32, 1.0, 1.0, 1.0];
+        low_pass(&mut seq, 1.0, 1.0);
+        assert_eq!(seq, [0.0, 0.5, 0.75, 0.875]);
+    }
+
+    #[test]
+    fn test_group_delay_on_ramp() {
+        let mut seq: [f64; 200] = core::array::from_fn(|n| n as f64);
+        low_pass(&mut seq, 0.01, 0.05);
+        let delay = group_delay(0.01, 0.05);
+        assert!((delay - 5.0).abs() < 1e-9);
+        // Once settled, the output trails the ramp by the delay.
+        assert!((199.0 - seq[199] - delay).abs() < 1e-6);
+
+        align(&mut seq, 0.01, 0.05);
+        for (n, &x) in seq.iter().enumerate().take(194).skip(100) {
+            assert!((x - n as f64).abs() < 1e-6);
+        }
+        assert_eq!(seq[199], seq[194]);
+    }
+
+    #[test]
+    fn test_low_pass_vec() {
+        let mut seq = [
+            [0.0f32, 2.0, -4.0],
+            [1.0, 2.0, 4.0],
+            [1.0, 2.0, 4.0],
+            [1.0, 2.0, 4.0],
+        ];
+        low_pass_vec(&mut seq, 1.0, 1.0);
+        assert_eq!(
+            seq,
+            [
+                [0.0, 2.0, -4.0],
+                [0.5, 2.0, 0.0],
+                [0.75, 2.0, 2.0],
+                [0.875, 2.0, 3.0],
+            ]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_decimate_lowpass() {
+        // A constant offset plus a component at the Nyquist frequency,
+        // which aliases onto the offset when decimating by two.
+        let seq: Vec<f32> = (0..200)
+            .map(|i| if i % 2 == 0 { 2.0 } else { 0.0 })
+            .collect();
+
+        let naive: Vec<f32> = seq.iter().copied().step_by(2).collect();
+        assert!(naive.iter().all(|&x| x == 2.0));
+
+        let decimated = decimate_lowpass(&seq, 2, 1.0, 10.0);
+        assert_eq!(decimated.len(), 100);
+        for x in &decimated[50..] {
+            assert!((x - 1.0).abs() < 0.1, "{x}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_minmax_decimate_keeps_spike() {
+        let mut seq = vec![0.0f32; 1000];
+        seq[637] = 5.0;
+        seq[100] = -1.0;
+
+        let decimated = minmax_decimate(&seq, 10);
+
+        assert_eq!(decimated.len(), 20);
+        assert_eq!(decimated[2..4], [-1.0, 0.0]);
+        assert_eq!(decimated[12..14], [0.0, 5.0]);
+        assert_eq!(decimated.iter().filter(|&&x| x != 0.0).count(), 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_minmax_decimate_uneven() {
+        let seq = [3.0f32, 1.0, 2.0, 4.0, 0.0];
+        assert_eq!(minmax_decimate(&seq, 2), [3.0, 1.0, 4.0, 0.0]);
+        assert!(minmax_decimate(&seq, 0).is_empty());
+        assert!(minmax_decimate(&[], 3).is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_minmax_decimate_more_buckets_than_samples() {
+        let seq = [3.0f32, 1.0, 2.0];
+        assert_eq!(minmax_decimate(&seq, 8), [3.0, 3.0, 1.0, 1.0, 2.0, 2.0]);
+        assert_eq!(minmax_decimate(&seq, usize::MAX / 2 + 1).len(), 6);
+        // 2 samples per chunk, so only 5 chunks.
+        assert_eq!(minmax_decimate(&[0.0; 10], 6).len(), 10);
+    }
+
+    #[test]
+    fn test_fir_moving_average() {
+        let input = [1.0f64, 4.0, 2.0, 8.0, 5.0, 7.0, 3.0, 6.0];
+        let mut seq = input;
+        fir(&mut seq, &[0.25; 4]);
+
+        // Zero history at the start.
+        assert_eq!(seq[..3], [0.25, 1.25, 1.75]);
+        for n in 3..input.len() {
+            let mean = input[n - 3..=n].iter().sum::<f64>() / 4.0;
+            assert!((seq[n] - mean).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_fir_delay() {
+        let mut seq = [1.0f32, 2.0, 3.0];
+        fir(&mut seq, &[0.0, 1.0]);
+        assert_eq!(seq, [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_biquad_difference_equation() {
+        let mut filter = Biquad::new(0.5f64, 0.25, 0.125, -0.5, 0.25);
+        let mut step = [1.0; 4];
+        filter.process_slice(&mut step);
+        // y[n] = 0.5 x[n] + 0.25 x[n-1] + 0.125 x[n-2]
+        //        + 0.5 y[n-1] - 0.25 y[n-2]
+        assert_eq!(step[0], 0.5);
+        assert_eq!(step[1], 0.75 + 0.25);
+        assert_eq!(step[2], 0.875 + 0.5 - 0.125);
+        assert_eq!(step[3], 0.875 + 0.625 - 0.25);
+
+        filter.reset();
+        assert_eq!(filter.process(1.0), 0.5);
+    }
+
+    #[test]
+    fn test_biquad_lowpass_step() {
+        let mut filter = Biquad::lowpass(10.0f64, core::f64::consts::FRAC_1_SQRT_2, 1000.0);
+        let mut step = [1.0; 500];
+        filter.process_slice(&mut step);
+        assert!((step[0] - filter.b0).abs() < 1e-12);
+        assert!(step[0] < 0.01);
+        // A Butterworth step response overshoots by about 4%.
+        let peak = step.iter().copied().fold(0.0, f64::max);
+        assert!((peak - 1.043).abs() < 0.005, "{peak}");
+        assert!((step[499] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_biquad_highpass_step() {
+        let mut filter = Biquad::highpass(10.0f64, core::f64::consts::FRAC_1_SQRT_2, 1000.0);
+        let mut step = [1.0; 500];
+        filter.process_slice(&mut step);
+        assert!((step[0] - filter.b0).abs() < 1e-12);
+        assert!(step[0] > 0.9);
+        assert!(step[499].abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_biquad_notch_passes_dc() {
+        let mut filter = Biquad::notch(50.0f32, 5.0, 1000.0);
+        let mut step = [1.0; 1000];
+        filter.process_slice(&mut step);
+        assert!((step[999] - 1.0).abs() < 1e-4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_filter_pipeline() {
+        let dead_band = |x: f64| if x.abs() < 0.2 { 0.0 } else { x };
+        let mut pipeline = FilterPipeline::new()
+            .stage(Biquad::highpass(1.0, 0.707, 100.0))
+            .stage(dead_band);
+
+        let mut highpass = Biquad::highpass(1.0, 0.707, 100.0);
+        for n in 0..200 {
+            let x = if n < 100 { 1.0 } else { -1.0 };
+            let expected = dead_band(highpass.process(x));
+            assert_eq!(pipeline.update(x), expected);
+        }
+
+        let mut empty = FilterPipeline::new();
+        assert_eq!(empty.update(3.0), 3.0);
+    }
+
+    #[test]
+    fn test_notch_two_tones() {
+        use core::f32::consts::TAU;
+
+        let tone = |f: f32, n: usize| (TAU * f * n as f32 / 1000.0).sin();
+        let mut seq: [f32; 2000] = core::array::from_fn(|n| tone(10.0, n) + tone(120.0, n));
+        notch_filter(&mut seq, 120.0, 5.0, 1000.0);
+
+        // After settling, only the 10 Hz tone is left, apart from a
+        // small phase shift.
+        let error = (1000..2000)
+            .map(|n| (seq[n] - tone(10.0, n)).abs())
+            .fold(0.0, f32::max);
+        assert!(error < 0.05, "{error}");
+    }
+
+    #[test]
+    fn test_lms_cancels_correlated_noise() {
+        use core::f32::consts::TAU;
+
+        let mut filter = LmsFilter::<f32, 4>::new(0.01);
+        let signal = |n: usize| 0.3 * (TAU * n as f32 / 500.0).sin();
+        let reference = |n: usize| (TAU * n as f32 / 20.0).sin();
+        // The noise is a scaled and phase-shifted copy of the
+        // reference.
+        let noise = |n: usize| 0.8 * (TAU * n as f32 / 20.0 + 1.0).sin();
+
+        let error = |filter: &mut LmsFilter<f32, 4>, range: core::ops::Range<usize>| {
+            range
+                .map(|n| (filter.update(signal(n) + noise(n), reference(n)) - signal(n)).abs())
+                .fold(0.0, f32::max)
+        };
+        assert!(error(&mut filter, 0..100) > 0.5);
+        error(&mut filter, 100..5000);
+        let residual = error(&mut filter, 5000..6000);
+        assert!(residual < 0.02, "{residual}");
+
+        filter.reset();
+        assert_eq!(filter.update(1.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_zero_crossing_frequency() {
+        use core::f64::consts::TAU;
+
+        // 7 Hz with a DC offset and some noise, sampled at 1 kHz.
+        let seq: [f64; 1000] = core::array::from_fn(|n| {
+            let t = n as f64 * 0.001;
+            let noise = if n % 2 == 0 { 0.05 } else { -0.05 };
+            2.0 + (TAU * 7.0 * t).sin() + noise
+        });
+        let frequency = zero_crossing_frequency(&seq, 0.001);
+        assert!((frequency - 7.0).abs() < 0.1, "{frequency}");
+
+        assert_eq!(zero_crossing_frequency(&[1.0f32; 8], 0.001), 0.0);
+        assert_eq!(zero_crossing_frequency::<f32>(&[], 0.001), 0.0);
+    }
+}
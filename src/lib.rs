@@ -16,6 +16,10 @@
 extern crate core;
 
 pub mod error;
+pub mod filter;
 pub mod log;
+mod rng;
+pub mod signals;
+pub mod stats;
 pub mod telemetry;
 pub mod transport;
@@ -17,29 +17,34 @@
 //!
 //! Log messages can have up to 8 named parameters bound. If trying to
 //! bind a ninth parameter, the [`Error::Saturated`] error is
-//! returned.
+//! returned. Use [`Log::with_field_lossy`] to drop a field instead.
+//!
+//! A placeholder can name a default to display if its field is not
+//! bound, as in `{fix:-none}`.
 
-use heapless::LinearMap;
-use serde::{Deserialize, Serialize};
+use heapless::{LinearMap, String};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::error::Error;
+use crate::telemetry::tick_delta;
+use crate::transport::encode;
 
 /// A log message.
+///
+/// Parameter names are `&'static str` by default. For names built at
+/// runtime, such as indexed sensor names, use another key type like
+/// `heapless::String<N>` via [`Log::keyed`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Log {
-    level: Level,
-    message: &'static str,
-    parameters: LinearMap<&'static str, LogParameter, 8>,
+pub struct Log<K: Eq = &'static str> {
+    pub(crate) level: Level,
+    pub(crate) message: &'static str,
+    pub(crate) parameters: LinearMap<K, LogParameter, 8>,
 }
 
 impl Log {
     /// Create a new log message.
     pub fn new(level: Level, message: &'static str) -> Self {
-        Self {
-            level,
-            message,
-            parameters: LinearMap::new(),
-        }
+        Self::keyed(level, message)
     }
 
     /// Create a new log message with the [`Level::Debug`] level.
@@ -61,12 +66,30 @@ impl Log {
     pub fn error(message: &'static str) -> Self {
         Self::new(Level::Error, message)
     }
+}
+
+impl<K: Eq + Clone> Log<K> {
+    /// Create a new log message with parameter names of type `K`.
+    ///
+    /// In debug builds, this panics if the braces in `message` are
+    /// unbalanced or a placeholder is not a valid identifier.
+    pub fn keyed(level: Level, message: &'static str) -> Self {
+        debug_assert!(
+            valid_placeholders(message),
+            "malformed placeholder in log message {message:?}"
+        );
+        Self {
+            level,
+            message,
+            parameters: LinearMap::new(),
+        }
+    }
 
     /// Add a field to the log message. This operation can fail if the
     /// log message is already saturated.
     pub fn with_field(
         mut self,
-        name: &'static str,
+        name: K,
         parameter: impl Into<LogParameter>,
     ) -> Result<Self, Error> {
         self.parameters
@@ -74,35 +97,375 @@ impl Log {
             .map(|_| self)
             .map_err(|_| Error::Saturated)
     }
-}
 
-#[cfg(feature = "std")]
-impl std::fmt::Display for Log {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut message = self.message.to_string();
-        let mut parameters = self.parameters.iter();
-        while let Some((name, parameter)) = parameters.next() {
-            match parameter {
-                LogParameter::String(s) => {
-                    message = message.replace(&format!("{{{}}}", name), s);
-                }
-                LogParameter::Float(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
-                }
-                LogParameter::Integer(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
+    /// Add a field to the log message. If the log message is already
+    /// saturated, a field is dropped according to `policy` instead of
+    /// returning an error.
+    pub fn with_field_lossy(
+        mut self,
+        name: K,
+        parameter: impl Into<LogParameter>,
+        policy: Saturation,
+    ) -> Self {
+        let parameter = parameter.into();
+        if self.parameters.contains_key(&name) || self.parameters.len() < self.parameters.capacity()
+        {
+            // Cannot fail, either the key exists or there is space.
+            let _ = self.parameters.insert(name, parameter);
+            return self;
+        }
+        match policy {
+            Saturation::DropNewest => {}
+            Saturation::DropOldest => {
+                // `LinearMap::remove` does not preserve insertion order,
+                // so rebuild the map without the first entry.
+                let mut parameters = LinearMap::new();
+                for (name, parameter) in self.parameters.iter().skip(1) {
+                    let _ = parameters.insert(name.clone(), parameter.clone());
                 }
-                LogParameter::UnsignedInteger(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
+                let _ = parameters.insert(name, parameter);
+                self.parameters = parameters;
+            }
+        }
+        self
+    }
+
+    /// Add the fields of a context to the log message. Fields already
+    /// bound to the message take precedence. Fails with
+    /// [`Error::Saturated`], leaving the message unchanged, if the
+    /// fields would not fit.
+    pub fn with_context(mut self, context: &LogContext<K>) -> Result<Self, Error> {
+        let new = context
+            .fields
+            .keys()
+            .filter(|name| !self.parameters.contains_key(name))
+            .count();
+        if self.parameters.len() + new > self.parameters.capacity() {
+            return Err(Error::Saturated);
+        }
+        for (name, parameter) in context.fields.iter() {
+            if !self.parameters.contains_key(name) {
+                // Cannot fail, there is room for every new field.
+                let _ = self.parameters.insert(name.clone(), parameter.clone());
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Fields shared by related log messages, such as the current mission
+/// phase, added with [`Log::with_context`].
+///
+/// ```
+/// # use postcard_telemetry::log::{Log, LogContext};
+/// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+/// let context = LogContext::new().with_field("phase", "descent")?;
+/// let message = Log::warning("high sink rate").with_context(&context)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogContext<K: Eq = &'static str> {
+    fields: LinearMap<K, LogParameter, 4>,
+}
+
+impl<K: Eq> LogContext<K> {
+    /// Create an empty context.
+    pub const fn new() -> Self {
+        Self {
+            fields: LinearMap::new(),
+        }
+    }
+
+    /// Add a field to the context, replacing one of the same name.
+    /// Contexts hold up to four fields, and this fails with
+    /// [`Error::Saturated`] beyond that.
+    pub fn with_field(
+        mut self,
+        name: K,
+        parameter: impl Into<LogParameter>,
+    ) -> Result<Self, Error> {
+        self.fields
+            .insert(name, parameter.into())
+            .map(|_| self)
+            .map_err(|_| Error::Saturated)
+    }
+}
+
+impl<K: Eq> Default for LogContext<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Serialize> Log<K> {
+    /// Serialize the log message into a buffer for transmission, like
+    /// [`encode`], but truncate the message to at most `max_len` bytes.
+    ///
+    /// Truncated messages end in `...`, which counts towards
+    /// `max_len`, and is itself cut short if `max_len` is less than
+    /// three bytes. This lets overly long messages degrade gracefully
+    /// rather than failing with [`Error::BufferTooSmall`]. Parameters
+    /// are sent in full.
+    pub fn encode_truncated<'b>(
+        &self,
+        max_len: usize,
+        buf: &'b mut [u8],
+    ) -> Result<&'b mut [u8], Error> {
+        encode(
+            &TruncatedLog {
+                level: self.level,
+                message: Truncated {
+                    message: self.message,
+                    max_len,
+                },
+                parameters: &self.parameters,
+            },
+            buf,
+        )
+    }
+}
+
+/// Check that every `{` in `message` is closed by a `}`, with an
+/// identifier in between, optionally followed by `:-` and a default,
+/// and that there are no stray `}`.
+fn valid_placeholders(message: &str) -> bool {
+    let mut rest = message;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return false;
+        }
+        let Some(len) = rest[start + 1..].find('}') else {
+            return false;
+        };
+        let placeholder = &rest[start + 1..start + 1 + len];
+        let name = placeholder
+            .split_once(":-")
+            .map_or(placeholder, |(name, _)| name);
+        let mut chars = name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return false;
+        }
+        rest = &rest[start + len + 2..];
+    }
+    true
+}
+
+/// A view of a [`Log`] with a truncated message, wire-compatible with
+/// it.
+#[derive(Serialize)]
+struct TruncatedLog<'a, K: Eq> {
+    level: Level,
+    message: Truncated<'a>,
+    parameters: &'a LinearMap<K, LogParameter, 8>,
+}
+
+/// A message truncated to at most `max_len` bytes including the
+/// ellipsis.
+struct Truncated<'a> {
+    message: &'a str,
+    max_len: usize,
+}
+
+impl Truncated<'_> {
+    const ELLIPSIS: &'static str = "...";
+}
+
+impl core::fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.message.len() <= self.max_len {
+            return f.write_str(self.message);
+        }
+        let Some(mut end) = self.max_len.checked_sub(Self::ELLIPSIS.len()) else {
+            return f.write_str(&Self::ELLIPSIS[..self.max_len]);
+        };
+        while !self.message.is_char_boundary(end) {
+            end -= 1;
+        }
+        f.write_str(&self.message[..end])?;
+        f.write_str(Self::ELLIPSIS)
+    }
+}
+
+impl Serialize for Truncated<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A log message received on a device without `std`, with strings of
+/// up to `K` bytes. See [`crate::transport::OwnedPackage`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedLog<const K: usize> {
+    pub level: Level,
+    pub message: String<K>,
+    pub parameters: LinearMap<String<K>, OwnedLogParameter<K>, 8>,
+}
+
+/// A received log message parameter, wire-compatible with
+/// [`LogParameter`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OwnedLogParameter<const K: usize> {
+    String(String<K>),
+    Float(f32),
+    Integer(i32),
+    UnsignedInteger(u32),
+}
+
+/// What to do when binding a field to a saturated log message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Saturation {
+    /// Ignore the new field.
+    DropNewest,
+    /// Evict the field that was bound first to make room.
+    DropOldest,
+}
+
+/// Coalesces floods of identical log messages.
+///
+/// The first log with a given level and message passes through and
+/// opens a window of `window` ticks. Repeats within the window are
+/// suppressed and counted, and once the window has closed, a single
+/// summary log `"{message} (repeated {count} times)"` reports them.
+/// Parameters are not compared, and those of suppressed logs are
+/// lost.
+///
+/// Up to `N` distinct messages are tracked at once. If all slots are
+/// taken by open windows, further messages pass through unlimited.
+///
+/// ```
+/// # use postcard_telemetry::log::{Log, RateLimitedLogger};
+/// let mut limiter = RateLimitedLogger::<4>::new(1000);
+/// let mut sent = 0;
+/// for now in 0..100 {
+///     limiter.log(Log::error("sensor timeout"), now, |_| sent += 1);
+/// }
+/// limiter.flush(1000, |_| sent += 1);
+/// // The first log, then the summary.
+/// assert_eq!(sent, 2);
+/// ```
+///
+/// Timestamps are ticks of a wrapping `u32` counter, see
+/// [`crate::telemetry::tick_delta`].
+pub struct RateLimitedLogger<const N: usize> {
+    window: u32,
+    /// The window start and suppressed count, by level and message.
+    windows: LinearMap<(Level, &'static str), (u32, u32), N>,
+}
+
+impl<const N: usize> RateLimitedLogger<N> {
+    /// Create a limiter coalescing repeats within `window` ticks.
+    pub const fn new(window: u32) -> Self {
+        Self {
+            window,
+            windows: LinearMap::new(),
+        }
+    }
+
+    /// Pass `log`, received at `now`, through the limiter. `emit` is
+    /// called with the logs to send: the summary of a closed window
+    /// for the same message, if any, and then `log` itself unless it
+    /// was suppressed.
+    pub fn log(&mut self, log: Log, now: u32, mut emit: impl FnMut(Log)) {
+        let key = (log.level, log.message);
+        if let Some((start, suppressed)) = self.windows.get_mut(&key) {
+            if tick_delta(*start, now) < self.window {
+                *suppressed = suppressed.saturating_add(1);
+                return;
+            }
+            if *suppressed > 0 {
+                emit(Self::summary(log.level, log.message, *suppressed));
+            }
+            *start = now;
+            *suppressed = 0;
+            emit(log);
+            return;
+        }
+        self.flush(now, &mut emit);
+        // If every slot holds an open window, pass through unlimited.
+        let _ = self.windows.insert(key, (now, 0));
+        emit(log);
+    }
+
+    /// Emit summaries for all windows that have closed at `now`, and
+    /// forget them. Call this periodically so that repeats are
+    /// reported even if the message stops.
+    pub fn flush(&mut self, now: u32, mut emit: impl FnMut(Log)) {
+        let window = self.window;
+        let mut closed: heapless::Vec<(Level, &'static str), N> = heapless::Vec::new();
+        for (&(level, message), (start, suppressed)) in self.windows.iter() {
+            if tick_delta(*start, now) >= window {
+                if *suppressed > 0 {
+                    emit(Self::summary(level, message, *suppressed));
                 }
+                // Cannot fail, there are at most `N` windows.
+                let _ = closed.push((level, message));
             }
         }
-        write!(f, "[{}] {}", self.level, message)
+        for key in closed {
+            self.windows.remove(&key);
+        }
+    }
+
+    fn summary(level: Level, message: &'static str, count: u32) -> Log {
+        let mut summary = Log::new(level, "{message} (repeated {count} times)");
+        // Cannot fail, the log is empty.
+        let _ = summary.parameters.insert("message", message.into());
+        let _ = summary.parameters.insert("count", count.into());
+        summary
     }
 }
 
-/// A log message level.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg(feature = "std")]
+impl<K: Eq + AsRef<str>> std::fmt::Display for Log<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] ", self.level)?;
+        write_message(f, self.message, |name| {
+            self.parameters
+                .iter()
+                .find(|(key, _)| key.as_ref() == name)
+                .map(|(_, parameter)| parameter)
+        })
+    }
+}
+
+/// Write `message` with every `{name}` placeholder replaced by the
+/// parameter bound to `name`. A placeholder of the form
+/// `{name:-default}` is replaced by `default` if nothing is bound,
+/// other unbound placeholders are written as-is.
+#[cfg(feature = "std")]
+pub(crate) fn write_message<'a, P: std::fmt::Display + 'a>(
+    f: &mut std::fmt::Formatter<'_>,
+    message: &str,
+    lookup: impl Fn(&str) -> Option<&'a P>,
+) -> std::fmt::Result {
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        f.write_str(&rest[..start])?;
+        let placeholder = &rest[start + 1..start + len];
+        let (name, default) = match placeholder.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (placeholder, None),
+        };
+        match (lookup(name), default) {
+            (Some(parameter), _) => write!(f, "{parameter}")?,
+            (None, Some(default)) => f.write_str(default)?,
+            (None, None) => f.write_str(&rest[start..=start + len])?,
+        }
+        rest = &rest[start + len + 1..];
+    }
+    f.write_str(rest)
+}
+
+/// A log message level, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Level {
     Debug,
     Info,
@@ -171,6 +534,8 @@ impl From<u32> for LogParameter {
 mod tests {
     use super::*;
 
+    use heapless::Vec;
+
     #[test]
     #[cfg(feature = "std")]
     fn test_display() {
@@ -180,6 +545,43 @@ mod tests {
         assert_eq!(format!("{}", LogParameter::UnsignedInteger(1)), "1");
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_display_defaults() {
+        let message = Log::info("gps {fix:-no fix}, {sats:-?} sats, {hdop}")
+            .with_field("sats", 7u32)
+            .unwrap();
+        assert_eq!(format!("{message}"), "[INFO] gps no fix, 7 sats, {hdop}");
+    }
+
+    #[test]
+    fn test_rate_limited_logger() {
+        let mut limiter = RateLimitedLogger::<2>::new(100);
+        let mut sent: Vec<Log, 8> = Vec::new();
+
+        for now in 0..50 {
+            limiter.log(Log::error("stuck"), now, |log| sent.push(log).unwrap());
+        }
+        limiter.log(Log::info("stuck"), 60, |log| sent.push(log).unwrap());
+        assert_eq!(sent.len(), 2);
+
+        // The next repeat after the window closes brings the summary.
+        limiter.log(Log::error("stuck"), 120, |log| sent.push(log).unwrap());
+        assert_eq!(sent.len(), 4);
+        let summary = &sent[2];
+        assert!(matches!(summary.level, Level::Error));
+        assert_eq!(summary.parameters["message"], LogParameter::String("stuck"));
+        assert_eq!(
+            summary.parameters["count"],
+            LogParameter::UnsignedInteger(49)
+        );
+        assert_eq!(sent[3].message, "stuck");
+
+        // Windows without repeats close silently.
+        limiter.flush(1000, |log| sent.push(log).unwrap());
+        assert_eq!(sent.len(), 4);
+    }
+
     #[test]
     fn test_with_field() {
         let message = Log::new(Level::Info, "foo {bar}")
@@ -189,6 +591,53 @@ mod tests {
         assert_eq!(message.parameters["bar"], LogParameter::String("baz"));
     }
 
+    #[test]
+    fn test_valid_placeholders() {
+        assert!(valid_placeholders("no placeholders"));
+        assert!(valid_placeholders("{a} and {_b2}"));
+        assert!(valid_placeholders("{a:-n/a} and {b:-}"));
+        assert!(!valid_placeholders("{:-n/a}"));
+        assert!(!valid_placeholders("x={x"));
+        assert!(!valid_placeholders("x=x}"));
+        assert!(!valid_placeholders("{}"));
+        assert!(!valid_placeholders("{2x}"));
+        assert!(!valid_placeholders("{a b}"));
+        assert!(!valid_placeholders("{{a}}"));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "malformed placeholder")]
+    fn test_unbalanced_placeholder_panics() {
+        let _ = Log::info("x={x");
+    }
+
+    #[test]
+    fn test_with_context() -> Result<(), Error> {
+        let context = LogContext::new()
+            .with_field("phase", "descent")?
+            .with_field("leg", 2u32)?;
+        let message = Log::info("{leg}: {event} during {phase}")
+            .with_field("event", "gear down")?
+            .with_field("leg", 3u32)?
+            .with_context(&context)?;
+        assert_eq!(message.parameters.len(), 3);
+        assert_eq!(message.parameters["phase"], LogParameter::String("descent"));
+        assert_eq!(message.parameters["leg"], LogParameter::UnsignedInteger(3));
+
+        let full = ["1", "2", "3", "4", "5", "6", "7"]
+            .into_iter()
+            .try_fold(Log::info("full"), |log, name| log.with_field(name, 0u32))?;
+        assert!(matches!(
+            full.clone().with_context(&context),
+            Err(Error::Saturated)
+        ));
+        let full = full.with_field("phase", "cruise")?;
+        let full = full.with_context(&context.with_field("phase", "descent")?);
+        assert!(matches!(full, Err(Error::Saturated)));
+        Ok(())
+    }
+
     #[test]
     fn test_with_field_saturated() -> Result<(), Error> {
         let message = Log::new(Level::Info, "foo {bar}")
@@ -206,4 +655,85 @@ mod tests {
         ));
         Ok(())
     }
+
+    #[test]
+    fn test_with_field_lossy() {
+        let names = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "10"];
+
+        let mut newest = Log::info("foo");
+        let mut oldest = Log::info("foo");
+        for (i, name) in names.into_iter().enumerate() {
+            newest = newest.with_field_lossy(name, i as u32, Saturation::DropNewest);
+            oldest = oldest.with_field_lossy(name, i as u32, Saturation::DropOldest);
+        }
+
+        let kept: Vec<_, 8> = newest.parameters.keys().copied().collect();
+        assert_eq!(kept, names[..8]);
+        let kept: Vec<_, 8> = oldest.parameters.keys().copied().collect();
+        assert_eq!(kept, names[2..]);
+        assert_eq!(oldest.parameters["10"], LogParameter::UnsignedInteger(9));
+    }
+
+    #[test]
+    fn test_with_field_lossy_replaces_existing() {
+        let mut message = Log::info("foo");
+        for name in ["1", "2", "3", "4", "5", "6", "7", "8"] {
+            message = message.with_field_lossy(name, 0u32, Saturation::DropOldest);
+        }
+        let message = message.with_field_lossy("1", 1u32, Saturation::DropOldest);
+        assert_eq!(message.parameters.len(), 8);
+        assert_eq!(message.parameters["1"], LogParameter::UnsignedInteger(1));
+    }
+
+    #[test]
+    fn test_encode_truncated() {
+        let message = Log::warning("this message is far too long for {buf}")
+            .with_field("buf", 16u32)
+            .unwrap();
+        let mut buf = [0u8; 32];
+        assert!(matches!(
+            encode(&message, &mut buf),
+            Err(Error::BufferTooSmall)
+        ));
+
+        let encoded = message.encode_truncated(16, &mut buf).unwrap();
+        let decoded: OwnedLog<16> = crate::transport::decode(encoded).unwrap();
+        assert_eq!(decoded.message, "this message ...");
+        assert_eq!(decoded.parameters.len(), 1);
+    }
+
+    #[test]
+    fn test_encode_truncated_short_message() {
+        let message = Log::info("short");
+        let mut buf = [0u8; 32];
+        let encoded = message.encode_truncated(5, &mut buf).unwrap();
+        let decoded: OwnedLog<16> = crate::transport::decode(encoded).unwrap();
+        assert_eq!(decoded.message, "short");
+
+        for max_len in 0..3 {
+            let encoded = message.encode_truncated(max_len, &mut buf).unwrap();
+            let decoded: OwnedLog<16> = crate::transport::decode(encoded).unwrap();
+            assert_eq!(decoded.message, "..."[..max_len]);
+        }
+    }
+
+    #[test]
+    fn test_runtime_keys() -> Result<(), Error> {
+        use core::fmt::Write;
+
+        let mut name = String::<16>::new();
+        write!(name, "imu{}", 2).unwrap();
+        let message = Log::keyed(Level::Warning, "{imu2} saturated")
+            .with_field(name.clone(), 16.0)?
+            .with_field_lossy("imu3".into(), 15.5, Saturation::DropNewest);
+
+        assert_eq!(message.parameters.len(), 2);
+        assert_eq!(message.parameters[&name], LogParameter::Float(16.0));
+
+        let mut buf = [0u8; 64];
+        let encoded = message.encode_truncated(64, &mut buf)?;
+        let decoded: OwnedLog<16> = crate::transport::decode(encoded)?;
+        assert_eq!(decoded.parameters[&name], OwnedLogParameter::Float(16.0));
+        Ok(())
+    }
 }
@@ -0,0 +1,19 @@
+//! Pseudo-random numbers for jitter and test signals
+
+/// A small, fast, deterministic pseudo-random number generator.
+#[derive(Debug, Clone)]
+pub(crate) struct XorShift32(u32);
+
+impl XorShift32 {
+    pub(crate) const fn new(seed: u32) -> Self {
+        // Xorshift gets stuck at zero.
+        Self(if seed == 0 { 0x9e37_79b9 } else { seed })
+    }
+
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+}
@@ -0,0 +1,99 @@
+//! Test signal generators
+//!
+//! Generators fill slices of samples, like the filters in
+//! [`crate::filter`], so they work on fixed-size buffers without
+//! allocating. Random signals are seeded, so tests using them are
+//! reproducible.
+
+use num_traits::{Float, FloatConst};
+
+use crate::rng::XorShift32;
+
+/// Fill `buf` with a unit sine wave of `frequency`, starting at zero
+/// phase. `dt` is the sample period, and `frequency` is in cycles per
+/// unit of `dt`.
+pub fn sine<T: Float + FloatConst>(buf: &mut [T], frequency: T, dt: T) {
+    for (n, x) in buf.iter_mut().enumerate() {
+        let t = T::from(n).unwrap() * dt;
+        *x = (T::TAU() * frequency * t).sin();
+    }
+}
+
+/// Fill `buf` with a unit step, zero before sample `at` and one from
+/// there on.
+pub fn step<T: Float>(buf: &mut [T], at: usize) {
+    for (n, x) in buf.iter_mut().enumerate() {
+        *x = if n < at { T::zero() } else { T::one() };
+    }
+}
+
+/// Fill `buf` with a unit sine wave whose frequency sweeps linearly
+/// from `start` at the first sample to `end` at the end of the
+/// buffer, for probing a filter's frequency response in one pass.
+pub fn chirp<T: Float + FloatConst>(buf: &mut [T], start: T, end: T, dt: T) {
+    let duration = T::from(buf.len()).unwrap() * dt;
+    let rate = (end - start) / duration;
+    let two = T::from(2).unwrap();
+    for (n, x) in buf.iter_mut().enumerate() {
+        let t = T::from(n).unwrap() * dt;
+        *x = (T::TAU() * (start * t + rate * t * t / two)).sin();
+    }
+}
+
+/// Fill `buf` with uniformly distributed white noise in `[-1, 1)`,
+/// generated by a xorshift generator seeded by `seed`. The same seed
+/// always produces the same samples.
+pub fn white_noise<T: Float>(buf: &mut [T], seed: u32) {
+    let mut rng = XorShift32::new(seed);
+    let scale = T::from(1u32 << 23).unwrap();
+    for x in buf.iter_mut() {
+        // The top 24 bits, as a fraction of 2^23 in [0, 2).
+        *x = T::from(rng.next_u32() >> 8).unwrap() / scale - T::one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::filter::zero_crossing_frequency;
+
+    #[test]
+    fn test_sine_period() {
+        let mut seq = [0.0f64; 1000];
+        sine(&mut seq, 7.0, 0.001);
+        assert_eq!(seq[0], 0.0);
+        assert!((seq[36] - 1.0).abs() < 1e-3);
+
+        let frequency = zero_crossing_frequency(&seq, 0.001);
+        assert!((frequency - 7.0).abs() < 0.1, "{frequency}");
+    }
+
+    #[test]
+    fn test_step_and_chirp() {
+        let mut seq = [0.0f32; 4];
+        step(&mut seq, 2);
+        assert_eq!(seq, [0.0, 0.0, 1.0, 1.0]);
+
+        // The average frequency of a linear sweep is its midpoint.
+        let mut seq = [0.0f64; 2000];
+        chirp(&mut seq, 5.0, 15.0, 0.001);
+        let frequency = zero_crossing_frequency(&seq, 0.001);
+        assert!((frequency - 10.0).abs() < 0.5, "{frequency}");
+    }
+
+    #[test]
+    fn test_white_noise() {
+        let (mut first, mut second) = ([0.0f32; 1000], [0.0f32; 1000]);
+        white_noise(&mut first, 42);
+        white_noise(&mut second, 42);
+        assert_eq!(first, second);
+
+        assert!(first.iter().all(|x| (-1.0..1.0).contains(x)));
+        let mean = first.iter().sum::<f32>() / first.len() as f32;
+        assert!(mean.abs() < 0.1, "{mean}");
+
+        white_noise(&mut second, 43);
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,393 @@
+//! Streaming statistics
+//!
+//! Estimators in this module summarize a stream of samples in
+//! constant memory, so that derived values such as percentiles can be
+//! recorded as telemetry without storing the samples themselves.
+
+/// A streaming quantile estimator using the P² algorithm by Jain and
+/// Chlamtac.
+///
+/// The estimator tracks five markers whose heights approximate the
+/// minimum, the maximum, the requested quantile, and the quantiles
+/// halfway to either end. Each update is O(1) and no samples are
+/// stored.
+///
+/// ```
+/// # use postcard_telemetry::stats::P2Quantile;
+/// # use postcard_telemetry::telemetry::TelemetryReporter;
+/// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+/// let mut p95 = P2Quantile::new(0.95);
+/// for loop_time in [1.2, 1.1, 1.4, 3.0, 1.2, 1.3] {
+///     p95.update(loop_time);
+/// }
+/// let mut reporter = TelemetryReporter::<8>::new();
+/// reporter.record("loop_p95", p95.estimate())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct P2Quantile {
+    quantile: f32,
+    count: usize,
+    heights: [f32; 5],
+    positions: [f32; 5],
+    desired: [f32; 5],
+    increments: [f32; 5],
+}
+
+impl P2Quantile {
+    /// Create a new estimator for the quantile `quantile`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is not in `[0, 1]`.
+    pub fn new(quantile: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile {quantile} is not in [0, 1]"
+        );
+        let p = quantile;
+        Self {
+            quantile: p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [0.0, 1.0, 2.0, 3.0, 4.0],
+            desired: [0.0, 2.0 * p, 4.0 * p, 2.0 + 2.0 * p, 4.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    /// Add a sample.
+    pub fn update(&mut self, x: f32) {
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                sort(&mut self.heights);
+            }
+            return;
+        }
+        self.count += 1;
+
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < q[i + 1]).unwrap_or(3)
+        };
+
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let n = &self.positions;
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = if d > 0.0 { 1.0 } else { -1.0 };
+                let parabolic = self.parabolic(i, d);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, d)
+                    };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    /// The current estimate of the quantile. With fewer than five
+    /// samples this is the nearest-rank quantile of the samples seen
+    /// so far, and `0.0` if there were none.
+    pub fn estimate(&self) -> f32 {
+        match self.count {
+            0 => 0.0,
+            1..=4 => {
+                let mut samples = [0.0; 5];
+                let samples = &mut samples[..self.count];
+                samples.copy_from_slice(&self.heights[..self.count]);
+                sort(samples);
+                let rank = self.quantile * (self.count - 1) as f32 + 0.5;
+                samples[rank as usize]
+            }
+            _ => self.heights[2],
+        }
+    }
+
+    /// The number of samples seen.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Piecewise-parabolic prediction for moving marker `i` by `d`.
+    fn parabolic(&self, i: usize, d: f32) -> f32 {
+        let q = &self.heights;
+        let n = &self.positions;
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    /// Linear prediction for moving marker `i` by `d`.
+    fn linear(&self, i: usize, d: f32) -> f32 {
+        let q = &self.heights;
+        let n = &self.positions;
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+    }
+}
+
+/// The median of the last `W` samples, updated incrementally.
+///
+/// The window is kept both in arrival order, to know which sample
+/// leaves next, and sorted, so each update is a binary search and a
+/// shift of at most `W` samples. This is useful for rejecting spikes
+/// in real time.
+///
+/// ```
+/// # use postcard_telemetry::stats::RollingMedian;
+/// let mut median = RollingMedian::<3>::new();
+/// median.update(1.0);
+/// median.update(100.0);
+/// assert_eq!(median.update(2.0), 2.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RollingMedian<const W: usize> {
+    window: [f32; W],
+    sorted: [f32; W],
+    len: usize,
+    next: usize,
+}
+
+impl<const W: usize> RollingMedian<W> {
+    /// Create an empty window.
+    pub const fn new() -> Self {
+        Self {
+            window: [0.0; W],
+            sorted: [0.0; W],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Add a sample, evicting the oldest one once the window is full,
+    /// and return the new median. A window of size zero always returns
+    /// `x`.
+    pub fn update(&mut self, x: f32) -> f32 {
+        if W == 0 {
+            return x;
+        }
+        if self.len == W {
+            let oldest = self.window[self.next];
+            let index = self.sorted[..self.len]
+                .binary_search_by(|probe| probe.total_cmp(&oldest))
+                .unwrap_or_else(|index| index);
+            self.sorted.copy_within(index + 1..self.len, index);
+            self.len -= 1;
+        }
+        let index = self.sorted[..self.len]
+            .binary_search_by(|probe| probe.total_cmp(&x))
+            .unwrap_or_else(|index| index);
+        self.sorted.copy_within(index..self.len, index + 1);
+        self.sorted[index] = x;
+        self.len += 1;
+
+        self.window[self.next] = x;
+        self.next = (self.next + 1) % W;
+        self.median()
+    }
+
+    /// The median of the samples in the window, averaging the middle
+    /// two for an even count, or `0.0` if there were none.
+    pub fn median(&self) -> f32 {
+        let sorted = &self.sorted[..self.len];
+        match self.len {
+            0 => 0.0,
+            len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+            len => sorted[len / 2],
+        }
+    }
+
+    /// The number of samples in the window.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no samples were added yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const W: usize> Default for RollingMedian<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A running sum kept in fixed point, for long integrations such as
+/// odometry where an `f32` sum stops absorbing small deltas.
+///
+/// Every delta is rounded to the nearest multiple of `1 / scale` and
+/// added to an `i64`, which saturates instead of wrapping. With a
+/// scale of a million, the sum stays exact to a micro-unit up to about
+/// nine trillion units.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    scale: u32,
+    sum: i64,
+}
+
+impl Accumulator {
+    /// Create an empty accumulator with a resolution of `1 / scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is zero.
+    pub const fn new(scale: u32) -> Self {
+        assert!(scale > 0, "scale must not be zero");
+        Self { scale, sum: 0 }
+    }
+
+    /// Add a delta. NaN is ignored, and infinite deltas saturate the
+    /// sum.
+    pub fn add(&mut self, delta: f32) {
+        let steps = num_traits::Float::round(delta as f64 * self.scale as f64);
+        // Float to integer casts saturate, and map NaN to zero.
+        self.sum = self.sum.saturating_add(steps as i64);
+    }
+
+    /// The current sum.
+    pub fn value(&self) -> f64 {
+        self.sum as f64 / self.scale as f64
+    }
+
+    /// Reset the sum to zero.
+    pub fn reset(&mut self) {
+        self.sum = 0;
+    }
+}
+
+/// Sort floats ascending, treating NaN as equal to anything.
+fn sort(samples: &mut [f32]) {
+    samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic uniform samples in `[0, 1)`.
+    fn uniform(seed: &mut u32) -> f32 {
+        *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (*seed >> 8) as f32 / (1 << 24) as f32
+    }
+
+    #[test]
+    fn test_p2_uniform() {
+        let mut seed = 42;
+        let mut p95 = P2Quantile::new(0.95);
+        let mut median = P2Quantile::new(0.5);
+        for _ in 0..10_000 {
+            let x = uniform(&mut seed);
+            p95.update(x);
+            median.update(x);
+        }
+        assert_eq!(p95.count(), 10_000);
+        assert!((p95.estimate() - 0.95).abs() < 0.01, "{}", p95.estimate());
+        assert!(
+            (median.estimate() - 0.5).abs() < 0.01,
+            "{}",
+            median.estimate()
+        );
+    }
+
+    #[test]
+    fn test_p2_extreme_quantiles() {
+        let mut min = P2Quantile::new(0.0);
+        let mut max = P2Quantile::new(1.0);
+        for x in [3.0, 1.0, 2.0] {
+            min.update(x);
+            max.update(x);
+        }
+        assert_eq!(min.estimate(), 1.0);
+        assert_eq!(max.estimate(), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in [0, 1]")]
+    fn test_p2_invalid_quantile_panics() {
+        let _ = P2Quantile::new(1.5);
+    }
+
+    #[test]
+    fn test_p2_few_samples() {
+        let mut median = P2Quantile::new(0.5);
+        assert_eq!(median.estimate(), 0.0);
+        median.update(3.0);
+        assert_eq!(median.estimate(), 3.0);
+        median.update(1.0);
+        median.update(2.0);
+        assert_eq!(median.estimate(), 2.0);
+    }
+
+    #[test]
+    fn test_rolling_median_matches_naive() {
+        const W: usize = 7;
+        let mut seed = 7;
+        let samples: [f32; 500] = core::array::from_fn(|_| (uniform(&mut seed) * 10.0).floor());
+
+        let mut median = RollingMedian::<W>::new();
+        assert_eq!(median.median(), 0.0);
+        for (i, &x) in samples.iter().enumerate() {
+            let start = (i + 1).saturating_sub(W);
+            let mut window = [0.0; W];
+            let window = &mut window[..i + 1 - start];
+            window.copy_from_slice(&samples[start..=i]);
+            sort(window);
+            let len = window.len();
+            let expected = if len % 2 == 0 {
+                (window[len / 2 - 1] + window[len / 2]) / 2.0
+            } else {
+                window[len / 2]
+            };
+            assert_eq!(median.update(x), expected, "sample {i}");
+        }
+        assert_eq!(median.len(), W);
+    }
+
+    #[test]
+    fn test_accumulator_beats_f32() {
+        let mut accumulator = Accumulator::new(1_000_000);
+        let mut naive = 0.0f32;
+        for _ in 0..1_000_000 {
+            accumulator.add(1e-4);
+            naive += 1e-4;
+        }
+        assert!((accumulator.value() - 100.0).abs() < 1e-9);
+        assert!((naive - 100.0).abs() > 0.1);
+
+        accumulator.add(f32::NAN);
+        assert!((accumulator.value() - 100.0).abs() < 1e-9);
+        accumulator.add(f32::INFINITY);
+        accumulator.add(1.0);
+        assert_eq!(accumulator.value(), i64::MAX as f64 / 1e6);
+
+        accumulator.reset();
+        assert_eq!(accumulator.value(), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "scale must not be zero")]
+    fn test_accumulator_zero_scale_panics() {
+        let _ = Accumulator::new(0);
+    }
+}
@@ -32,37 +32,229 @@
 //!    out to mission control. This also clears all data, so the
 //!    reporter can be reused in a loop.
 
-use heapless::LinearMap;
-use serde::{Deserialize, Serialize};
+use heapless::{LinearMap, String};
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::error::Error;
 
 /// A global telemetry reporter with a static size of data points.
 /// Once the reporter capacity has been reached, no more data can be
 /// recorded until it is cleared.
-pub struct TelemetryReporter<const N: usize> {
+///
+/// Optional features take their own capacities, which default to zero
+/// so that reporters not using them take no memory for them:
+///
+/// - `R` ranges for [`TelemetryReporter::record_ranged`]
+/// - `T` timestamps for [`TelemetryReporter::record_at`]
+/// - `I` running sums for [`TelemetryReporter::integrate`]
+pub struct TelemetryReporter<
+    const N: usize,
+    const R: usize = 0,
+    const T: usize = 0,
+    const I: usize = 0,
+> {
     telemetry: TelemetryFrame<N>,
+    timestamps: LinearMap<&'static str, u32, T>,
+    ranges: LinearMap<&'static str, (f32, f32), R>,
+    integrals: LinearMap<&'static str, f64, I>,
+    epoch: u32,
 }
 
-impl<const N: usize> TelemetryReporter<N> {
+impl<const N: usize, const R: usize, const T: usize, const I: usize> TelemetryReporter<N, R, T, I> {
     /// Create a new telemetry reporter.
     pub const fn new() -> Self {
         Self {
             telemetry: LinearMap::new(),
+            timestamps: LinearMap::new(),
+            ranges: LinearMap::new(),
+            integrals: LinearMap::new(),
+            epoch: 0,
         }
     }
 
-    /// Record a data point. Will return [`Error::Saturated`] if the
-    /// recorder is full.
-    pub fn record(
+    /// Register the valid range `min..=max` for a key, to be checked
+    /// by [`TelemetryReporter::record_ranged`]. Registering a key again
+    /// replaces its range. Will return [`Error::Saturated`] if `R`
+    /// ranges are already registered.
+    pub fn register_range(&mut self, name: &'static str, min: f32, max: f32) -> Result<(), Error> {
+        self.ranges
+            .insert(name, (min, max))
+            .map(|_| ())
+            .map_err(|_| Error::Saturated)
+    }
+
+    /// Record a data point, rejecting numeric values outside of the
+    /// range registered for `name` with [`Error::OutOfRange`]. Keys
+    /// without a registered range are recorded as usual. Will return
+    /// [`Error::Saturated`] if the recorder is full.
+    pub fn record_ranged(
         &mut self,
         name: &'static str,
-        value: impl Into<DataPoint> + Copy,
+        value: impl TelemetryValue,
     ) -> Result<(), Error> {
+        let value = value.into();
+        if let Some(&(min, max)) = self.ranges.get(&name) {
+            let v = match value {
+                DataPoint::F32(v) => v,
+                DataPoint::I32(v) => v as f32,
+                DataPoint::U32(v) => v as f32,
+                DataPoint::Normalized(v) => denormalize(v),
+                DataPoint::Str(_) => return self.record(name, value),
+            };
+            if !(min..=max).contains(&v) {
+                return Err(Error::OutOfRange);
+            }
+        }
+        self.record(name, value)
+    }
+
+    /// Record a data point. Will return [`Error::Saturated`] if the
+    /// recorder is full. This drops any timestamp previously recorded
+    /// for `name` with [`TelemetryReporter::record_at`].
+    pub fn record(&mut self, name: &'static str, value: impl TelemetryValue) -> Result<(), Error> {
         self.telemetry
             .insert(name, value.into())
-            .map(|_| ())
-            .map_err(|_| Error::Saturated)
+            .map_err(|_| Error::Saturated)?;
+        self.timestamps.remove(&name);
+        Ok(())
+    }
+
+    /// Record several data points at once. Either all of them are
+    /// recorded, or, if they would not fit, none are and
+    /// [`Error::Saturated`] is returned. Like
+    /// [`TelemetryReporter::record`], this drops earlier timestamps of
+    /// the recorded keys.
+    ///
+    /// ```
+    /// # use postcard_telemetry::telemetry::TelemetryReporter;
+    /// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+    /// let mut reporter = TelemetryReporter::<8>::new();
+    /// reporter.batch(|b| {
+    ///     b.record("roll", 0.1).record("pitch", -0.2);
+    ///     b.record("yaw", 1.5);
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch(&mut self, f: impl FnOnce(&mut Batch<N>)) -> Result<(), Error> {
+        let mut batch = Batch {
+            staged: TelemetryFrame::new(),
+            overflowed: false,
+        };
+        f(&mut batch);
+        let new_keys = batch
+            .staged
+            .keys()
+            .filter(|name| !self.telemetry.contains_key(name))
+            .count();
+        if batch.overflowed || new_keys > self.remaining() {
+            return Err(Error::Saturated);
+        }
+        for (name, value) in batch.staged.iter() {
+            // Cannot fail, there is room for every new key.
+            let _ = self.telemetry.insert(name, *value);
+            self.timestamps.remove(name);
+        }
+        Ok(())
+    }
+
+    /// Record a data point sampled at `timestamp`, for sensors that
+    /// update at different times within a tick. Use
+    /// [`TelemetryReporter::report_timestamped`] to report the
+    /// timestamps. Will return [`Error::Saturated`] if the recorder is
+    /// full, or if `T` timestamps are already held.
+    pub fn record_at(
+        &mut self,
+        name: &'static str,
+        value: impl TelemetryValue,
+        timestamp: u32,
+    ) -> Result<(), Error> {
+        if self.timestamps.len() == T && !self.timestamps.contains_key(&name) {
+            return Err(Error::Saturated);
+        }
+        self.record(name, value)?;
+        // Cannot fail, there is room for the timestamp.
+        let _ = self.timestamps.insert(name, timestamp);
+        Ok(())
+    }
+
+    /// Record a floating point data point, rejecting NaN and infinite
+    /// values with [`Error::NonFinite`]. Will return
+    /// [`Error::Saturated`] if the recorder is full.
+    pub fn record_finite(&mut self, name: &'static str, value: f32) -> Result<(), Error> {
+        if !value.is_finite() {
+            return Err(Error::NonFinite);
+        }
+        self.record(name, value)
+    }
+
+    /// Increment a counter data point by `by`, starting from zero if
+    /// it has not been recorded yet. The counter saturates at
+    /// [`u32::MAX`]. A data point of another type under the same name
+    /// is replaced. Will return [`Error::Saturated`] if the recorder is
+    /// full.
+    pub fn increment(&mut self, name: &'static str, by: u32) -> Result<(), Error> {
+        let count = match self.telemetry.get(&name) {
+            Some(DataPoint::U32(count)) => count.saturating_add(by),
+            _ => by,
+        };
+        self.record(name, count)
+    }
+
+    /// Integrate `rate` over `dt` into a running sum, and record the
+    /// sum as an [`DataPoint::F32`].
+    ///
+    /// The sum is kept as an `f64` and is not cleared by reporting, so
+    /// it does not drift even over long runs where `f32` steps would
+    /// be lost to rounding.
+    ///
+    /// Will return [`Error::Saturated`] without updating anything if
+    /// `I` other keys are already integrated, see
+    /// [`TelemetryReporter::reset_integral`]. If only the recorder is
+    /// full, the sum is still updated, and [`Error::Saturated`] is
+    /// returned as well.
+    pub fn integrate(&mut self, name: &'static str, rate: f32, dt: f32) -> Result<(), Error> {
+        let sum = self.integrals.get(&name).copied().unwrap_or(0.0) + rate as f64 * dt as f64;
+        self.integrals
+            .insert(name, sum)
+            .map_err(|_| Error::Saturated)?;
+        self.record(name, sum as f32)
+    }
+
+    /// The running sum kept by [`TelemetryReporter::integrate`] for
+    /// `name`.
+    pub fn integral(&self, name: &'static str) -> Option<f64> {
+        self.integrals.get(&name).copied()
+    }
+
+    /// Stop integrating `name`, returning its final sum. The next
+    /// [`TelemetryReporter::integrate`] starts from zero again.
+    pub fn reset_integral(&mut self, name: &'static str) -> Option<f64> {
+        self.integrals.remove(&name)
+    }
+
+    /// Stop integrating all keys, see
+    /// [`TelemetryReporter::reset_integral`].
+    pub fn clear_integrals(&mut self) {
+        self.integrals.clear();
+    }
+
+    /// The keys recorded since the last report, in the order they
+    /// were first recorded.
+    pub fn dirty_keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.telemetry.keys().copied()
+    }
+
+    /// The number of additional distinct keys that can be recorded
+    /// before the reporter is full.
+    pub fn remaining(&self) -> usize {
+        N - self.telemetry.len()
+    }
+
+    /// Returns `true` if no further distinct keys can be recorded.
+    pub fn is_full(&self) -> bool {
+        self.telemetry.len() == N
     }
 
     /// Report the current telemetry data. This will clear the
@@ -71,19 +263,213 @@ impl<const N: usize> TelemetryReporter<N> {
     pub fn report(&mut self) -> TelemetryFrame<N> {
         let rv = self.telemetry.clone();
         self.telemetry.clear();
+        self.timestamps.clear();
         rv
     }
+
+    /// Start a new session at tick `now`. Timestamps reported by
+    /// [`TelemetryReporter::report_timestamped`] are ticks since the
+    /// epoch, computed with [`tick_delta`], so plots of a session start
+    /// at zero even if the tick counter wrapped. The epoch is zero
+    /// until set.
+    pub fn set_epoch(&mut self, now: u32) {
+        self.epoch = now;
+    }
+
+    /// Report the current telemetry data and encode the frame into
+    /// `buf` with [`crate::transport::encode`]. The telemetry data is
+    /// only cleared if encoding succeeds, so nothing is lost if `buf`
+    /// is too small.
+    pub fn report_encoded<'b>(&mut self, buf: &'b mut [u8]) -> Result<&'b mut [u8], Error> {
+        let encoded = crate::transport::encode(&self.telemetry, buf)?;
+        self.telemetry.clear();
+        self.timestamps.clear();
+        Ok(encoded)
+    }
+
+    /// Report the current telemetry data with a timestamp for every
+    /// data point. Data points recorded without
+    /// [`TelemetryReporter::record_at`] are stamped with `now`.
+    /// Timestamps are relative to the epoch, see
+    /// [`TelemetryReporter::set_epoch`]. This will clear the telemetry
+    /// data.
+    ///
+    /// Timestamped frames cost an extra varint per data point on the
+    /// wire.
+    #[must_use]
+    pub fn report_timestamped(&mut self, now: u32) -> TimestampedFrame<N> {
+        let mut rv = TimestampedFrame::new();
+        for (name, value) in self.telemetry.iter() {
+            let timestamp = self.timestamps.get(name).copied().unwrap_or(now);
+            // Cannot fail, the capacities are the same.
+            let _ = rv.insert(*name, (tick_delta(self.epoch, timestamp), *value));
+        }
+        self.telemetry.clear();
+        self.timestamps.clear();
+        rv
+    }
+}
+
+/// Data points staged by [`TelemetryReporter::batch`].
+pub struct Batch<const N: usize> {
+    staged: TelemetryFrame<N>,
+    overflowed: bool,
+}
+
+impl<const N: usize> Batch<N> {
+    /// Stage a data point. Errors are reported when the batch is
+    /// committed.
+    pub fn record(&mut self, name: &'static str, value: impl TelemetryValue) -> &mut Self {
+        if self.staged.insert(name, value.into()).is_err() {
+            self.overflowed = true;
+        }
+        self
+    }
+}
+
+impl<const N: usize, const R: usize, const T: usize, const I: usize> Default
+    for TelemetryReporter<N, R, T, I>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Count the distinct keys in `keys`, to size a [`TelemetryReporter`]
+/// exactly for a fixed key set at compile time. See also
+/// [`telemetry_reporter!`](crate::telemetry_reporter).
+///
+/// ```
+/// # use postcard_telemetry::telemetry::{key_count, TelemetryReporter};
+/// const KEYS: &[&str] = &["altitude", "speed", "heading"];
+/// let reporter = TelemetryReporter::<{ key_count(KEYS) }>::new();
+/// ```
+pub const fn key_count(keys: &[&str]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < keys.len() {
+        let mut j = 0;
+        while j < i && !const_str_eq(keys[i], keys[j]) {
+            j += 1;
+        }
+        if j == i {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Create a [`TelemetryReporter`] with capacity for exactly the given
+/// keys.
+///
+/// ```
+/// # use postcard_telemetry::telemetry_reporter;
+/// let mut reporter = telemetry_reporter!("altitude", "speed");
+/// reporter.record("altitude", 120.0).unwrap();
+/// assert_eq!(reporter.remaining(), 1);
+/// ```
+#[macro_export]
+macro_rules! telemetry_reporter {
+    ($($key:expr),* $(,)?) => {
+        $crate::telemetry::TelemetryReporter::<
+            { $crate::telemetry::key_count(&[$($key),*]) },
+        >::new()
+    };
+}
+
+/// Compute the number of ticks elapsed from `a` to `b` on a wrapping
+/// `u32` tick counter.
+///
+/// This assumes `b` was taken after `a` and that less than one full
+/// counter period (2³² ticks) has elapsed between the two, so that
+/// the counter wrapped at most once.
+pub const fn tick_delta(a: u32, b: u32) -> u32 {
+    b.wrapping_sub(a)
 }
 
 /// A telemetry frame.
 pub type TelemetryFrame<const N: usize> = LinearMap<&'static str, DataPoint, N>;
 
+/// A telemetry frame with a timestamp for every data point.
+pub type TimestampedFrame<const N: usize> = LinearMap<&'static str, (u32, DataPoint), N>;
+
 /// A single data point.
+///
+/// String data points are sent in full with every frame, a length
+/// byte plus the string itself, so they are much more expensive than
+/// the numeric ones. Keep them short and use them sparingly.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum DataPoint {
     F32(f32),
     I32(i32),
     U32(u32),
+    Str(&'static str),
+    /// A fraction in `[0, 1]`, scaled to `0..=u16::MAX`. See
+    /// [`DataPoint::normalized`].
+    Normalized(u16),
+}
+
+impl DataPoint {
+    /// A fraction in `[0, 1]`, sent in two bytes. Values outside of
+    /// the range are clamped, and NaN becomes zero. The quantization
+    /// step is `1 / 65535`.
+    pub fn normalized(value: f32) -> Self {
+        // Float to integer casts truncate, saturate, and map NaN to
+        // zero, so adding a half rounds to the nearest step.
+        Self::Normalized((value * u16::MAX as f32 + 0.5) as u16)
+    }
+
+    /// The value as four little-endian bytes and a type tag, for
+    /// protocols that cannot use postcard. The tags are the variant
+    /// indices: 0 for `F32`, 1 for `I32`, 2 for `U32`, and 4 for
+    /// `Normalized`, whose two bytes are followed by two zero bytes.
+    /// Strings do not fit and return `None`.
+    pub fn to_le_bytes(&self) -> Option<([u8; 4], u8)> {
+        match *self {
+            DataPoint::F32(v) => Some((v.to_le_bytes(), 0)),
+            DataPoint::I32(v) => Some((v.to_le_bytes(), 1)),
+            DataPoint::U32(v) => Some((v.to_le_bytes(), 2)),
+            DataPoint::Str(_) => None,
+            DataPoint::Normalized(v) => Some(((v as u32).to_le_bytes(), 4)),
+        }
+    }
+
+    /// The inverse of [`DataPoint::to_le_bytes`]. Returns
+    /// [`Error::InvalidData`] for unknown tags, and for normalized
+    /// values that do not fit into two bytes.
+    pub fn from_le_bytes(tag: u8, bytes: [u8; 4]) -> Result<Self, Error> {
+        match tag {
+            0 => Ok(DataPoint::F32(f32::from_le_bytes(bytes))),
+            1 => Ok(DataPoint::I32(i32::from_le_bytes(bytes))),
+            2 => Ok(DataPoint::U32(u32::from_le_bytes(bytes))),
+            4 => u16::try_from(u32::from_le_bytes(bytes))
+                .map(DataPoint::Normalized)
+                .map_err(|_| Error::InvalidData),
+            _ => Err(Error::InvalidData),
+        }
+    }
+}
+
+/// Convert the raw value of a [`DataPoint::Normalized`] back into a
+/// fraction in `[0, 1]`.
+pub fn denormalize(raw: u16) -> f32 {
+    raw as f32 / u16::MAX as f32
 }
 
 impl From<f32> for DataPoint {
@@ -104,10 +490,367 @@ impl From<u32> for DataPoint {
     }
 }
 
+impl From<&'static str> for DataPoint {
+    fn from(value: &'static str) -> Self {
+        Self::Str(value)
+    }
+}
+
+/// A view of a telemetry frame that encodes [`DataPoint::F32`] values
+/// as integers, for ground stations without floating point support.
+///
+/// Every float is multiplied by `scale` and rounded to the nearest
+/// `i32`, saturating at its bounds. The scale is sent along with the
+/// frame, and the result is the frame with each float's payload
+/// replaced by a varint. Hosts decode it as
+/// `crate::transport::telemetry::ScaledFrame`.
+///
+/// ```
+/// # use postcard_telemetry::telemetry::{ScaledFrame, TelemetryReporter};
+/// # use postcard_telemetry::transport::encode;
+/// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+/// let mut reporter = TelemetryReporter::<8>::new();
+/// reporter.record("altitude", 123.456)?;
+/// let frame = reporter.report();
+/// // Millimeter resolution.
+/// let bytes = encode(&ScaledFrame::new(&frame, 1000), &mut [0; 64])?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ScaledFrame<'a, const N: usize> {
+    scale: u32,
+    frame: &'a TelemetryFrame<N>,
+}
+
+impl<'a, const N: usize> ScaledFrame<'a, N> {
+    /// Encode the floats in `frame` as multiples of `1 / scale`.
+    pub fn new(frame: &'a TelemetryFrame<N>, scale: u32) -> Self {
+        Self { scale, frame }
+    }
+}
+
+impl<const N: usize> Serialize for ScaledFrame<'_, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ScaledFrame", 2)?;
+        s.serialize_field("scale", &self.scale)?;
+        s.serialize_field("frame", &ScaledValues(self))?;
+        s.end()
+    }
+}
+
+struct ScaledValues<'a, const N: usize>(&'a ScaledFrame<'a, N>);
+
+impl<const N: usize> Serialize for ScaledValues<'_, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let scale = self.0.scale as f32;
+        let mut map = serializer.serialize_map(Some(self.0.frame.len()))?;
+        for (name, value) in self.0.frame.iter() {
+            match *value {
+                DataPoint::F32(v) => {
+                    let scaled = num_traits::Float::round(v * scale) as i32;
+                    map.serialize_entry(name, &ScaledF32(scaled))?
+                }
+                _ => map.serialize_entry(name, value)?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// A [`DataPoint::F32`] with an integer payload.
+struct ScaledF32(i32);
+
+impl Serialize for ScaledF32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant("DataPoint", 0, "F32", &self.0)
+    }
+}
+
+/// How to reduce the precision of floats, see [`quantize`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quantization {
+    /// Round to the nearest multiple of the step.
+    Step(f32),
+    /// Round to this many significant decimal digits, at least one.
+    SignificantDigits(u8),
+}
+
+/// Reduce the precision of `value`, so that repeated near-identical
+/// values encode identically. Zero, NaN, and infinite values are
+/// returned unchanged, as are values for which the step is not
+/// positive and finite, e.g. because more significant digits are
+/// requested than an `f32` can represent.
+pub fn quantize(value: f32, quantization: Quantization) -> f32 {
+    use num_traits::Float;
+
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let step = match quantization {
+        Quantization::Step(step) => step,
+        Quantization::SignificantDigits(digits) => {
+            let exponent = Float::floor(Float::log10(Float::abs(value))) as i32;
+            Float::powi(10.0f32, exponent + 1 - digits.max(1) as i32)
+        }
+    };
+    if !(step > 0.0 && step.is_finite()) {
+        return value;
+    }
+    let quantized = Float::round(value / step) * step;
+    if quantized.is_finite() {
+        quantized
+    } else {
+        value
+    }
+}
+
+/// A view of a telemetry frame that reduces the precision of
+/// [`DataPoint::F32`] values before encoding, see [`quantize`]. The
+/// result is an ordinary frame, so receivers need no changes.
+pub struct QuantizedFrame<'a, const N: usize> {
+    quantization: Quantization,
+    frame: &'a TelemetryFrame<N>,
+}
+
+impl<'a, const N: usize> QuantizedFrame<'a, N> {
+    /// Quantize the floats in `frame` when encoding.
+    pub fn new(frame: &'a TelemetryFrame<N>, quantization: Quantization) -> Self {
+        Self {
+            quantization,
+            frame,
+        }
+    }
+}
+
+impl<const N: usize> Serialize for QuantizedFrame<'_, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.frame.len()))?;
+        for (name, value) in self.frame.iter() {
+            match *value {
+                DataPoint::F32(v) => {
+                    map.serialize_entry(name, &DataPoint::F32(quantize(v, self.quantization)))?
+                }
+                _ => map.serialize_entry(name, value)?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// A table of up to `N` telemetry keys, each identified on the wire
+/// by its index. Send the table once at startup, then send frames as
+/// [`InternedFrame`]s, which replace keys by their ids. Hosts resolve
+/// the ids with `transport::telemetry::KeyDictionary`.
+///
+/// ```
+/// # use postcard_telemetry::telemetry::{InternedFrame, KeyTable, TelemetryReporter};
+/// # use postcard_telemetry::transport::encode;
+/// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+/// let mut keys = KeyTable::<8>::new();
+/// keys.register("altitude")?;
+/// // Send encode(&keys, ..) at startup.
+///
+/// let mut reporter = TelemetryReporter::<8>::new();
+/// reporter.record("altitude", 123.4)?;
+/// let frame = reporter.report();
+/// let bytes = encode(&InternedFrame::new(&frame, &keys)?, &mut [0; 64])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyTable<const N: usize> {
+    keys: heapless::Vec<&'static str, N>,
+}
+
+impl<const N: usize> KeyTable<N> {
+    /// Create an empty table.
+    pub const fn new() -> Self {
+        Self {
+            keys: heapless::Vec::new(),
+        }
+    }
+
+    /// Register a key, returning its id. Registering a key again
+    /// returns the same id. Will return [`Error::Saturated`] if the
+    /// table is full.
+    pub fn register(&mut self, key: &'static str) -> Result<u16, Error> {
+        if let Some(id) = self.id(key) {
+            return Ok(id);
+        }
+        let id = u16::try_from(self.keys.len()).map_err(|_| Error::Saturated)?;
+        self.keys.push(key).map_err(|_| Error::Saturated)?;
+        Ok(id)
+    }
+
+    /// The id of a registered key.
+    pub fn id(&self, key: &str) -> Option<u16> {
+        self.keys.iter().position(|k| *k == key).map(|id| id as u16)
+    }
+
+    /// All registered keys, in id order.
+    pub fn keys(&self) -> &[&'static str] {
+        &self.keys
+    }
+}
+
+impl<const N: usize> Default for KeyTable<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A view of a telemetry frame that sends the ids from a [`KeyTable`]
+/// in place of keys.
+pub struct InternedFrame<'a, const N: usize, const K: usize> {
+    frame: &'a TelemetryFrame<N>,
+    keys: &'a KeyTable<K>,
+}
+
+impl<'a, const N: usize, const K: usize> InternedFrame<'a, N, K> {
+    /// Replace the keys of `frame` by their ids in `keys` when
+    /// encoding. Will return [`Error::UnknownKey`] if any key of the
+    /// frame is not registered.
+    pub fn new(frame: &'a TelemetryFrame<N>, keys: &'a KeyTable<K>) -> Result<Self, Error> {
+        if frame.keys().any(|key| keys.id(key).is_none()) {
+            return Err(Error::UnknownKey);
+        }
+        Ok(Self { frame, keys })
+    }
+}
+
+impl<const N: usize, const K: usize> Serialize for InternedFrame<'_, N, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.frame.len()))?;
+        for (name, value) in self.frame.iter() {
+            let id = self
+                .keys
+                .id(name)
+                .ok_or_else(|| serde::ser::Error::custom("unregistered key"))?;
+            map.serialize_entry(&id, value)?;
+        }
+        map.end()
+    }
+}
+
+/// A value that can be recorded as telemetry.
+///
+/// This trait is sealed, so the set of recordable types is fixed by
+/// this crate, and types that would be expensive on the wire cannot
+/// sneak in through a `From` implementation:
+///
+/// ```compile_fail
+/// # use postcard_telemetry::telemetry::{DataPoint, TelemetryReporter};
+/// #[derive(Clone, Copy)]
+/// struct Position([f32; 3]);
+///
+/// impl From<Position> for DataPoint {
+///     fn from(position: Position) -> Self {
+///         DataPoint::F32(position.0[0])
+///     }
+/// }
+///
+/// let mut reporter = TelemetryReporter::<8>::new();
+/// reporter.record("position", Position([1.0, 2.0, 3.0]));
+/// ```
+pub trait TelemetryValue: sealed::Sealed + Into<DataPoint> + Copy {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_telemetry_value {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl TelemetryValue for $t {}
+        )*
+    };
+}
+
+impl_telemetry_value!(DataPoint, f32, i32, u32, &'static str, i8, i16, u8, u16);
+
+/// A telemetry frame received on a device without `std`, with keys of
+/// up to `K` bytes. See [`crate::transport::OwnedPackage`].
+pub type OwnedTelemetryFrame<const N: usize, const K: usize> =
+    LinearMap<String<K>, OwnedDataPoint<K>, N>;
+
+/// A single received data point, wire-compatible with [`DataPoint`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OwnedDataPoint<const K: usize> {
+    F32(f32),
+    I32(i32),
+    U32(u32),
+    Str(String<K>),
+    Normalized(u16),
+}
+
+/// A received timestamped telemetry frame, see
+/// [`OwnedTelemetryFrame`].
+pub type OwnedTimestampedFrame<const N: usize, const K: usize> =
+    LinearMap<String<K>, (u32, OwnedDataPoint<K>), N>;
+
+/// Lossless conversions from narrower integers.
+macro_rules! impl_from_widening {
+    ($variant:ident($wide:ty): $($t:ty),*) => {
+        $(
+            impl From<$t> for DataPoint {
+                fn from(value: $t) -> Self {
+                    Self::$variant(<$wide>::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_widening!(I32(i32): i8, i16);
+impl_from_widening!(U32(u32): u8, u16);
+
+/// Checked conversions from wider integers, returning
+/// [`Error::Overflow`] if the value does not fit.
+macro_rules! impl_try_from_narrowing {
+    ($variant:ident($narrow:ty): $($t:ty),*) => {
+        $(
+            impl TryFrom<$t> for DataPoint {
+                type Error = Error;
+
+                fn try_from(value: $t) -> Result<Self, Self::Error> {
+                    <$narrow>::try_from(value)
+                        .map(Self::$variant)
+                        .map_err(|_| Error::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_narrowing!(I32(i32): i64, isize);
+impl_try_from_narrowing!(U32(u32): u64, usize);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_key_table() -> Result<(), Error> {
+        let mut keys = KeyTable::<2>::new();
+        assert_eq!(keys.register("roll")?, 0);
+        assert_eq!(keys.register("pitch")?, 1);
+        assert_eq!(keys.register("roll")?, 0);
+        assert!(matches!(keys.register("yaw"), Err(Error::Saturated)));
+        assert_eq!(keys.id("pitch"), Some(1));
+        assert_eq!(keys.keys(), ["roll", "pitch"]);
+
+        let mut frame = TelemetryFrame::<4>::new();
+        frame.insert("roll", 0.1.into()).unwrap();
+        assert!(InternedFrame::new(&frame, &keys).is_ok());
+        frame.insert("yaw", 1.5.into()).unwrap();
+        assert!(matches!(
+            InternedFrame::new(&frame, &keys),
+            Err(Error::UnknownKey)
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_roundtrip() {
         let mut reporter = TelemetryReporter::<1>::new();
@@ -116,11 +859,33 @@ mod tests {
         assert_eq!(*result.get("tau").unwrap(), 6.12.into());
     }
 
+    #[test]
+    fn test_data_point_wire_round_trip() {
+        use crate::transport::{decode, encode};
+
+        // Borrowed strings must outlive the data point, so decoding
+        // into `DataPoint` needs a `'static` buffer.
+        static ENCODED: [u8; 5] = [0, 0, 0, 0x80, 0x3f];
+        let mut buf = [0u8; 8];
+        let encoded = postcard::to_slice(&DataPoint::F32(1.0), &mut buf).unwrap();
+        assert_eq!(encoded, ENCODED);
+        let decoded: DataPoint = postcard::from_bytes(&ENCODED).unwrap();
+        assert_eq!(decoded, DataPoint::F32(1.0));
+
+        // Otherwise, decode into the wire-compatible `OwnedDataPoint`.
+        let encoded = encode(&DataPoint::F32(1.0), &mut buf).unwrap();
+        let decoded: OwnedDataPoint<8> = decode(encoded).unwrap();
+        assert_eq!(decoded, OwnedDataPoint::F32(1.0));
+    }
+
     #[test]
     fn test_graceful_when_full() -> Result<(), Error> {
         let mut reporter = TelemetryReporter::<1>::new();
         reporter.record("tau", 6.12)?;
-        assert!(matches!(reporter.record("pi", 3.14), Err(Error::Saturated)));
+        assert!(matches!(
+            reporter.record("pi", core::f32::consts::PI),
+            Err(Error::Saturated)
+        ));
         assert_eq!(reporter.telemetry.len(), 1);
         assert!(reporter.telemetry.contains_key(&"tau"));
         Ok(())
@@ -133,4 +898,352 @@ mod tests {
         let _ = reporter.report();
         assert!(reporter.telemetry.is_empty());
     }
+
+    #[test]
+    fn test_batch_is_atomic() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<3, 0, 1>::new();
+        reporter.record_at("a", 1u32, 5)?;
+        reporter.batch(|b| {
+            b.record("a", 2u32).record("b", 2u32);
+        })?;
+        assert_eq!(reporter.telemetry["a"], DataPoint::U32(2));
+        assert!(reporter.timestamps.is_empty());
+
+        let result = reporter.batch(|b| {
+            b.record("a", 3u32).record("c", 3u32).record("d", 3u32);
+        });
+        assert!(matches!(result, Err(Error::Saturated)));
+        assert_eq!(reporter.remaining(), 1);
+        assert_eq!(reporter.telemetry["a"], DataPoint::U32(2));
+        assert!(!reporter.telemetry.contains_key(&"c"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_encoded() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<2>::new();
+        reporter.record("foo", 1.0)?;
+        reporter.record("bar", 2u32)?;
+
+        let mut small = [0u8; 4];
+        assert!(matches!(
+            reporter.report_encoded(&mut small),
+            Err(Error::BufferTooSmall)
+        ));
+        assert_eq!(reporter.remaining(), 0);
+
+        let mut expected = [0u8; 32];
+        let len = crate::transport::encode(&reporter.telemetry, &mut expected)?.len();
+        let mut buf = [0u8; 32];
+        assert_eq!(reporter.report_encoded(&mut buf)?, &expected[..len]);
+        assert_eq!(reporter.remaining(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_timestamped() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<2, 0, 1>::new();
+        reporter.record_at("gyro", 0.5, 1003)?;
+        reporter.record("baro", 101.3)?;
+        let report = reporter.report_timestamped(1010);
+        assert_eq!(report["gyro"], (1003, DataPoint::F32(0.5)));
+        assert_eq!(report["baro"], (1010, DataPoint::F32(101.3)));
+        assert!(reporter.timestamps.is_empty());
+
+        reporter.record_at("gyro", 0.5, 1003)?;
+        let _ = reporter.report();
+        reporter.record("gyro", 0.6)?;
+        assert_eq!(reporter.report_timestamped(2000)["gyro"].0, 2000);
+
+        // A plain record replaces the timestamp along with the value.
+        reporter.record_at("gyro", 0.5, 3003)?;
+        reporter.record("gyro", 0.7)?;
+        assert_eq!(
+            reporter.report_timestamped(3010)["gyro"],
+            (3010, DataPoint::F32(0.7))
+        );
+
+        reporter.record_at("gyro", 0.5, 4003)?;
+        assert!(matches!(
+            reporter.record_at("baro", 101.3, 4005),
+            Err(Error::Saturated)
+        ));
+        assert!(!reporter.telemetry.contains_key(&"baro"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_relative_to_epoch() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<2, 0, 1>::new();
+        reporter.set_epoch(u32::MAX - 4);
+        reporter.record_at("gps", 1u32, u32::MAX)?;
+        reporter.record("imu", 2u32)?;
+
+        let report = reporter.report_timestamped(10);
+        assert_eq!(report["gps"], (4, DataPoint::U32(1)));
+        assert_eq!(report["imu"], (15, DataPoint::U32(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_finite() {
+        let mut reporter = TelemetryReporter::<2>::new();
+        assert!(matches!(
+            reporter.record_finite("nan", f32::NAN),
+            Err(Error::NonFinite)
+        ));
+        assert!(matches!(
+            reporter.record_finite("inf", f32::INFINITY),
+            Err(Error::NonFinite)
+        ));
+        assert!(reporter.telemetry.is_empty());
+        reporter.record_finite("g", 9.81).unwrap();
+        assert_eq!(reporter.telemetry["g"], DataPoint::F32(9.81));
+    }
+
+    #[test]
+    fn test_increment() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<2>::new();
+        reporter.increment("can_errors", 2)?;
+        reporter.increment("can_errors", 3)?;
+        reporter.record("overflows", u32::MAX - 1)?;
+        reporter.increment("overflows", 5)?;
+        let report = reporter.report();
+        assert_eq!(report["can_errors"], DataPoint::U32(5));
+        assert_eq!(report["overflows"], DataPoint::U32(u32::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_ranged() -> Result<(), Error> {
+        assert!(matches!(
+            TelemetryReporter::<2>::new().register_range("battery_v", 0.0, 30.0),
+            Err(Error::Saturated)
+        ));
+
+        let mut reporter = TelemetryReporter::<2, 1>::new();
+        reporter.register_range("battery_v", 0.0, 30.0)?;
+        reporter.record_ranged("battery_v", 24.2)?;
+        assert!(matches!(
+            reporter.record_ranged("battery_v", 41.0),
+            Err(Error::OutOfRange)
+        ));
+        assert!(matches!(
+            reporter.record_ranged("battery_v", -1i32),
+            Err(Error::OutOfRange)
+        ));
+        assert!(matches!(
+            reporter.record_ranged("battery_v", f32::NAN),
+            Err(Error::OutOfRange)
+        ));
+        assert_eq!(reporter.telemetry["battery_v"], DataPoint::F32(24.2));
+        reporter.record_ranged("unregistered", 1e9)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_dirty_keys() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<4>::new();
+        reporter.record("a", 1u32)?;
+        reporter.record("c", 1u32)?;
+        reporter.record("a", 2u32)?;
+        assert!(reporter.dirty_keys().eq(["a", "c"]));
+
+        let _ = reporter.report();
+        assert_eq!(reporter.dirty_keys().count(), 0);
+        reporter.increment("b", 1)?;
+        assert!(reporter.dirty_keys().eq(["b"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_remaining_capacity() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<3>::new();
+        assert_eq!(reporter.remaining(), 3);
+        assert!(!reporter.is_full());
+        reporter.record("a", 1.0)?;
+        reporter.record("b", 2.0)?;
+        assert_eq!(reporter.remaining(), 1);
+        reporter.record("b", 3.0)?;
+        assert_eq!(reporter.remaining(), 1);
+        assert!(!reporter.is_full());
+        reporter.record("c", 4.0)?;
+        assert_eq!(reporter.remaining(), 0);
+        assert!(reporter.is_full());
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_conversions() {
+        assert_eq!(DataPoint::from(200u8), DataPoint::U32(200));
+        assert_eq!(DataPoint::from(-300i16), DataPoint::I32(-300));
+        assert_eq!(DataPoint::try_from(7usize).unwrap(), DataPoint::U32(7));
+        assert_eq!(
+            DataPoint::try_from(-1_000_000i64).unwrap(),
+            DataPoint::I32(-1_000_000)
+        );
+        assert!(matches!(
+            DataPoint::try_from(i64::from(i32::MAX) + 1),
+            Err(Error::Overflow)
+        ));
+        assert!(matches!(
+            DataPoint::try_from(u64::MAX),
+            Err(Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_key_count() {
+        const KEYS: [&str; 3] = ["a", "b", "ab"];
+        const _: () = assert!(key_count(&KEYS) == 3);
+        const _: () = assert!(key_count(&["a", "b", "a", ""]) == 3);
+        const _: () = assert!(key_count(&[]) == 0);
+
+        let reporter = crate::telemetry_reporter!("a", "b", "ab");
+        assert_eq!(reporter.remaining(), KEYS.len());
+    }
+
+    #[test]
+    fn test_integrate() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<2, 0, 0, 1>::new();
+        // 0.1 m/s at 1 kHz for a million ticks. Summing the 1e-4 m
+        // steps in f32 would be off by more than half a meter.
+        for _ in 0..1_000_000 {
+            reporter.integrate("distance", 0.1, 0.001)?;
+        }
+        let expected = 0.1f32 as f64 * 0.001f32 as f64 * 1_000_000.0;
+        let sum = reporter.integral("distance").unwrap();
+        assert!((sum - expected).abs() < 1e-6, "{sum}");
+
+        let report = reporter.report();
+        assert_eq!(report["distance"], DataPoint::F32(sum as f32));
+        assert_eq!(reporter.integral("distance"), Some(sum));
+
+        reporter.integrate("distance", 1.0, 1.0)?;
+        assert_eq!(
+            reporter.report()["distance"],
+            DataPoint::F32((sum + 1.0) as f32)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_integrate_saturated() -> Result<(), Error> {
+        let mut reporter = TelemetryReporter::<1, 0, 0, 1>::new();
+        reporter.integrate("distance", 2.0, 1.0)?;
+        assert!(matches!(
+            reporter.integrate("energy", 1.0, 1.0),
+            Err(Error::Saturated)
+        ));
+        assert_eq!(reporter.integral("energy"), None);
+
+        // The recorder is full, but the sum is kept.
+        let _ = reporter.report();
+        reporter.record("speed", 2.0)?;
+        assert!(matches!(
+            reporter.integrate("distance", 2.0, 1.0),
+            Err(Error::Saturated)
+        ));
+        assert_eq!(reporter.integral("distance"), Some(4.0));
+
+        assert_eq!(reporter.reset_integral("distance"), Some(4.0));
+        let _ = reporter.report();
+        reporter.integrate("energy", 1.0, 1.0)?;
+        reporter.clear_integrals();
+        assert_eq!(reporter.integral("energy"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantize() {
+        assert_eq!(quantize(1.26, Quantization::Step(0.5)), 1.5);
+        assert_eq!(quantize(-1.26, Quantization::Step(0.5)), -1.5);
+        assert_eq!(quantize(1.26, Quantization::Step(0.0)), 1.26);
+        assert_eq!(quantize(0.0, Quantization::SignificantDigits(2)), 0.0);
+        assert!(quantize(f32::NAN, Quantization::SignificantDigits(2)).is_nan());
+        assert_eq!(quantize(1.26, Quantization::Step(f32::INFINITY)), 1.26);
+        assert_eq!(quantize(1.26, Quantization::Step(f32::NAN)), 1.26);
+        // These steps underflow to zero.
+        assert_eq!(quantize(1.26, Quantization::SignificantDigits(255)), 1.26);
+        assert_eq!(quantize(1e-30, Quantization::SignificantDigits(20)), 1e-30);
+
+        for (value, expected) in [(123.456, 120.0), (0.012345, 0.012), (-9.87, -9.9)] {
+            let quantized = quantize(value, Quantization::SignificantDigits(2));
+            assert!(
+                (quantized - expected).abs() <= expected.abs() * 1e-6,
+                "{quantized}"
+            );
+        }
+        let quantized = quantize(98765.0, Quantization::SignificantDigits(0));
+        assert_eq!(quantized, 100000.0);
+
+        // Quantizing is reversible to within half a step.
+        for i in 0..1000 {
+            let value = i as f32 * 0.0173 - 5.0;
+            assert!((quantize(value, Quantization::Step(0.1)) - value).abs() <= 0.05 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_quantized_frame() -> Result<(), Error> {
+        use crate::transport::{decode, encode};
+
+        let mut frame = TelemetryFrame::<2>::new();
+        frame.insert("alt", DataPoint::F32(123.456)).unwrap();
+        frame.insert("count", DataPoint::U32(7)).unwrap();
+
+        let mut buf = [0u8; 32];
+        let encoded = encode(
+            &QuantizedFrame::new(&frame, Quantization::SignificantDigits(2)),
+            &mut buf,
+        )?;
+        let decoded: OwnedTelemetryFrame<2, 8> = decode(encoded)?;
+        assert_eq!(decoded[&"alt".into()], OwnedDataPoint::F32(120.0));
+        assert_eq!(decoded[&"count".into()], OwnedDataPoint::U32(7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_le_bytes_round_trip() {
+        for value in [
+            DataPoint::F32(-1.5),
+            DataPoint::I32(-7),
+            DataPoint::U32(0xdead_beef),
+            DataPoint::Normalized(0x1234),
+        ] {
+            let (bytes, tag) = value.to_le_bytes().unwrap();
+            assert_eq!(DataPoint::from_le_bytes(tag, bytes).unwrap(), value);
+        }
+        assert_eq!(DataPoint::U32(1).to_le_bytes(), Some(([1, 0, 0, 0], 2)));
+        assert_eq!(DataPoint::Str("no").to_le_bytes(), None);
+        assert!(matches!(
+            DataPoint::from_le_bytes(3, [0; 4]),
+            Err(Error::InvalidData)
+        ));
+        assert!(matches!(
+            DataPoint::from_le_bytes(4, [0, 0, 1, 0]),
+            Err(Error::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn test_tick_delta() {
+        assert_eq!(tick_delta(100, 250), 150);
+        assert_eq!(tick_delta(u32::MAX - 9, 10), 20);
+        assert_eq!(tick_delta(7, 7), 0);
+    }
+
+    #[test]
+    fn test_normalized() {
+        assert_eq!(DataPoint::normalized(0.0), DataPoint::Normalized(0));
+        assert_eq!(DataPoint::normalized(1.0), DataPoint::Normalized(u16::MAX));
+        assert_eq!(DataPoint::normalized(-0.5), DataPoint::Normalized(0));
+        assert_eq!(DataPoint::normalized(7.0), DataPoint::Normalized(u16::MAX));
+        assert_eq!(DataPoint::normalized(f32::NAN), DataPoint::Normalized(0));
+
+        let DataPoint::Normalized(raw) = DataPoint::normalized(0.5) else {
+            panic!("not a normalized data point");
+        };
+        assert!((denormalize(raw) - 0.5).abs() <= 0.5 / u16::MAX as f32);
+    }
 }
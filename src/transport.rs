@@ -11,14 +11,33 @@
 //!
 //! The included [`Package`] enum changes type depending on the `std`
 //! feature, so that each platform can use the most appropriate type.
-//! They are wire-compatible.
+//! They are wire-compatible. Devices without `std` can receive
+//! packages as [`OwnedPackage`], which copies strings into
+//! fixed-capacity storage.
+//!
+//! The [`reliability`] submodule adds sequence numbers, [`Package::Ack`]
+//! acknowledgements, and retransmission for at-least-once delivery.
+//!
+//! With the `compression` feature, the `compress` submodule offers
+//! an optional run-length compression layer for slow links.
+//!
+//! With the `std` feature, the `replay` submodule merges recorded
+//! logs and telemetry into a single timeline.
+//!
+//! With the `dashboard` feature, the `dashboard` submodule dispatches
+//! decoded packages as events for live host-side UIs.
 
-#[cfg(feature = "std")]
-use serde::Deserialize;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+#[cfg(feature = "compression")]
+pub mod compress;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
 #[cfg(feature = "std")]
 pub mod log;
+pub mod reliability;
+#[cfg(feature = "std")]
+pub mod replay;
 #[cfg(feature = "std")]
 pub mod telemetry;
 
@@ -29,30 +48,306 @@ pub fn encode<'b>(item: &impl Serialize, buf: &'b mut [u8]) -> Result<&'b mut [u
     postcard::to_slice_cobs(item, buf).map_err(|_| Error::BufferTooSmall)
 }
 
+/// Serialize several items into one buffer, each in its own COBS
+/// frame, to send them in a single transmission. Returns the used
+/// part of the buffer.
+///
+/// The items share a type, so to batch different kinds of messages
+/// wrap them in a [`Package`]. Receivers can split the batch on the
+/// zero delimiters, or feed it to a streaming decoder such as
+/// `postcard::accumulator::CobsAccumulator`.
+pub fn encode_batch<'b, T: Serialize>(
+    items: &[T],
+    buf: &'b mut [u8],
+) -> Result<&'b mut [u8], Error> {
+    let mut len = 0;
+    for item in items {
+        len += encode(item, &mut buf[len..])?.len();
+    }
+    Ok(&mut buf[..len])
+}
+
+/// Serialize an item into an owned vector of up to `N` bytes, which
+/// can be queued or passed around independently of any scratch
+/// buffer.
+pub fn encode_heapless<const N: usize>(
+    item: &impl Serialize,
+) -> Result<heapless::Vec<u8, N>, Error> {
+    postcard::to_vec_cobs(item).map_err(|_| Error::BufferTooSmall)
+}
+
+/// Compute the length of an item's framed encoding, as returned by
+/// [`encode`], without writing it anywhere. Useful for budgeting
+/// bandwidth before committing to a transmission. Errors are
+/// reported like those of [`encode`].
+pub fn encoded_size(item: &impl Serialize) -> Result<usize, Error> {
+    postcard::serialize_with_flavor(item, CobsSize::default()).map_err(|_| Error::BufferTooSmall)
+}
+
+/// A postcard flavor that only counts the bytes COBS framing would
+/// produce.
+struct CobsSize {
+    /// Bytes so far, including the leading code byte.
+    len: usize,
+    /// The code of the current COBS block, one more than its length.
+    code: u8,
+}
+
+impl Default for CobsSize {
+    fn default() -> Self {
+        Self { len: 1, code: 1 }
+    }
+}
+
+impl postcard::ser_flavors::Flavor for CobsSize {
+    type Output = usize;
+
+    fn try_push(&mut self, data: u8) -> postcard::Result<()> {
+        self.len += 1;
+        if data == 0 {
+            self.code = 1;
+        } else {
+            self.code += 1;
+            // A full block gets closed with an extra code byte.
+            if self.code == 0xFF {
+                self.len += 1;
+                self.code = 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<usize> {
+        // Account for the sentinel.
+        Ok(self.len + 1)
+    }
+}
+
+/// An encoder that owns a scratch buffer of `N` bytes and reuses it
+/// for every encoded item.
+///
+/// ```
+/// # use postcard_telemetry::transport::Encoder;
+/// # fn main() -> Result<(), postcard_telemetry::error::Error> {
+/// let mut encoder = Encoder::<64>::new();
+/// let bytes = encoder.encode(&42u32)?;
+/// // Send bytes somewhere
+/// # Ok(())
+/// # }
+/// ```
+pub struct Encoder<const N: usize> {
+    buf: [u8; N],
+}
+
+impl<const N: usize> Encoder<N> {
+    /// Create a new encoder.
+    pub const fn new() -> Self {
+        Self { buf: [0; N] }
+    }
+
+    /// Serialize an item into the internal buffer, returning the
+    /// framed bytes. The returned slice is only valid until the next
+    /// call.
+    pub fn encode(&mut self, item: &impl Serialize) -> Result<&[u8], Error> {
+        encode(item, &mut self.buf).map(|buf| &*buf)
+    }
+}
+
+impl<const N: usize> Default for Encoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A decoder that copies frames into a reusable internal buffer, for
+/// decoding from shared slices without allocating for every frame.
+///
+/// The buffer grows to the largest frame seen and is kept afterwards.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct Decoder {
+    buf: Vec<u8>,
+}
+
 #[cfg(feature = "std")]
-/// Deserialize an item from a buffer.
+impl Decoder {
+    /// Create a new decoder with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new decoder with room for frames of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Deserialize an item from a frame, see [`decode`]. The frame is
+    /// left untouched.
+    pub fn decode<T: DeserializeOwned>(&mut self, frame: &[u8]) -> Result<T, Error> {
+        self.buf.clear();
+        self.buf.extend_from_slice(frame);
+        decode(&mut self.buf)
+    }
+}
+
+/// The largest frame [`decode`] accepts on the host, in bytes.
+pub const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// Deserialize an item from a buffer. With `std`, where decoded items
+/// allocate, frames longer than [`MAX_FRAME_LEN`] are rejected, see
+/// [`decode_bounded`]. Without `std`, the buffer already bounds the
+/// frame.
 pub fn decode<'a, T>(buf: &'a mut [u8]) -> Result<T, Error>
 where
-    T: serde::Deserialize<'a>,
+    T: Deserialize<'a>,
 {
-    postcard::from_bytes_cobs(buf).map_err(|_| Error::InvalidData)
+    let max_len = if cfg!(feature = "std") {
+        MAX_FRAME_LEN
+    } else {
+        usize::MAX
+    };
+    decode_bounded(buf, max_len)
+}
+
+/// Deserialize an item from a buffer, rejecting frames longer than
+/// `max_len` bytes with [`Error::FrameTooLong`] before doing any work.
+/// Frames that end before the item is complete fail with
+/// [`Error::Truncated`].
+///
+/// Decoding is linear in the frame length, and length prefixes are
+/// never trusted beyond the bytes actually present: strings are read
+/// from the frame, and preallocation for collections on the host is
+/// capped by serde. Bounding the frame length therefore bounds the
+/// work and memory spent on adversarial input.
+pub fn decode_bounded<'a, T>(buf: &'a mut [u8], max_len: usize) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let len = frame_len(buf);
+    if len > max_len {
+        return Err(Error::FrameTooLong);
+    }
+    postcard::from_bytes_cobs(&mut buf[..len]).map_err(decode_error)
+}
+
+/// Deserialize an item from a shared frame like [`decode`], by first
+/// copying the frame into `scratch`, which strings of the item then
+/// borrow from. Will return [`Error::DecodeBufferTooSmall`] if the
+/// frame does not fit into `scratch`.
+pub fn decode_copy<'a, T>(frame: &[u8], scratch: &'a mut [u8]) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let len = frame_len(frame);
+    let scratch = scratch.get_mut(..len).ok_or(Error::DecodeBufferTooSmall)?;
+    scratch.copy_from_slice(&frame[..len]);
+    decode(scratch)
+}
+
+/// Deserialize an item from a buffer like [`decode`], and also return
+/// a copy of the postcard bytes it was decoded from, without the COBS
+/// framing, for debugging.
+#[cfg(feature = "std")]
+pub fn decode_with_raw<'a, T>(buf: &'a mut [u8]) -> Result<(T, Vec<u8>), Error>
+where
+    T: Deserialize<'a>,
+{
+    let len = frame_len(buf);
+    if len > MAX_FRAME_LEN {
+        return Err(Error::FrameTooLong);
+    }
+    let raw_len = cobs::decode_in_place(&mut buf[..len]).map_err(|_| Error::InvalidData)?;
+    let raw: &'a [u8] = &buf[..raw_len];
+    let item = postcard::from_bytes(raw).map_err(decode_error)?;
+    Ok((item, raw.to_vec()))
+}
+
+/// Map a postcard deserialization error to [`Error::Truncated`] if the
+/// data ended early, and [`Error::InvalidData`] otherwise.
+pub(crate) fn decode_error(error: postcard::Error) -> Error {
+    match error {
+        postcard::Error::DeserializeUnexpectedEnd => Error::Truncated,
+        _ => Error::InvalidData,
+    }
+}
+
+/// The length of the first frame in `buf`, including the delimiter.
+fn frame_len(buf: &[u8]) -> usize {
+    buf.iter()
+        .position(|&b| b == 0)
+        .map_or(buf.len(), |end| end + 1)
+}
+
+/// A serialization and framing backend for items of type `T`.
+///
+/// This allows generic code to swap backends without changing call
+/// sites. The trait is object safe, so it can also be used as
+/// `&dyn Transport<T>`. Decoded items own their data; use [`decode`]
+/// directly to borrow strings from the buffer.
+pub trait Transport<T> {
+    /// Serialize an item into a buffer, returning the framed bytes.
+    fn encode<'b>(&self, item: &T, buf: &'b mut [u8]) -> Result<&'b mut [u8], Error>;
+
+    /// Deserialize an item from a framed buffer.
+    fn decode(&self, buf: &mut [u8]) -> Result<T, Error>;
+}
+
+/// The default backend, postcard with COBS framing, as used by
+/// [`encode`] and [`decode`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostcardCobs;
+
+impl<T: Serialize + DeserializeOwned> Transport<T> for PostcardCobs {
+    fn encode<'b>(&self, item: &T, buf: &'b mut [u8]) -> Result<&'b mut [u8], Error> {
+        encode(item, buf)
+    }
+
+    fn decode(&self, buf: &mut [u8]) -> Result<T, Error> {
+        decode(buf)
+    }
 }
 
 #[cfg(not(feature = "std"))]
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize)]
 /// A package that can be sent or received.
+///
+/// Variants are encoded by their index, so their order is part of the
+/// wire format. Only ever append new variants.
 pub enum Package<const N: usize> {
     Log(crate::log::Log),
     Telemetry(crate::telemetry::TelemetryFrame<N>),
+    Ack(reliability::Ack),
+    TimestampedTelemetry(crate::telemetry::TimestampedFrame<N>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// A package received on a device without `std`.
+///
+/// Received strings cannot be `'static`, so they are copied into
+/// owned strings of up to `K` bytes each. Frames can hold up to `N`
+/// data points. Decoding fails with [`Error::InvalidData`] if either
+/// is exceeded.
+pub enum OwnedPackage<const N: usize, const K: usize> {
+    Log(crate::log::OwnedLog<K>),
+    Telemetry(crate::telemetry::OwnedTelemetryFrame<N, K>),
+    Ack(reliability::Ack),
+    TimestampedTelemetry(crate::telemetry::OwnedTimestampedFrame<N, K>),
 }
 
 #[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// A package that can be sent or received.
+///
+/// Variants are encoded by their index, so their order is part of the
+/// wire format. Only ever append new variants.
 pub enum Package {
     Log(log::Log),
     Telemetry(telemetry::TelemetryFrame),
+    Ack(reliability::Ack),
+    TimestampedTelemetry(telemetry::TimestampedFrame),
 }
 
 #[cfg(test)]
@@ -71,6 +366,366 @@ mod tests {
         assert_eq!(result.unwrap(), map);
     }
 
+    #[test]
+    fn test_encoded_size() {
+        let mut buf = [0u8; 1024];
+        // Long enough to span several COBS blocks.
+        static LONG: [u8; 600] = [b'a'; 600];
+        let long = core::str::from_utf8(&LONG).unwrap();
+
+        let mut frames: [crate::telemetry::TelemetryFrame<4>; 4] =
+            core::array::from_fn(|_| Default::default());
+        frames[1].insert("zero", 0u32.into()).unwrap();
+        frames[2].insert("voltage", 3.3f32.into()).unwrap();
+        frames[2].insert("ticks", (-1i32).into()).unwrap();
+        frames[2].insert("state", "armed".into()).unwrap();
+        frames[3].insert(long, 255u32.into()).unwrap();
+        for frame in &frames {
+            let expected = encode(frame, &mut buf).unwrap().len();
+            assert_eq!(encoded_size(frame).unwrap(), expected);
+        }
+        for len in [0, 253, 254, 255, 508, 509] {
+            let bytes = &LONG[..len];
+            let expected = encode(&bytes, &mut buf).unwrap().len();
+            assert_eq!(encoded_size(&bytes).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_encoder_reuses_buffer() {
+        let mut encoder = Encoder::<32>::new();
+
+        let first = encoder.encode(&[1u8, 2, 3]).unwrap();
+        assert_eq!(first, encode(&[1u8, 2, 3], &mut [0; 32]).unwrap());
+
+        let second = encoder.encode(&"foo").unwrap();
+        assert_eq!(second, encode(&"foo", &mut [0; 32]).unwrap());
+    }
+
+    #[test]
+    fn test_encode_heapless() {
+        let log = crate::log::Log::info("booted in {ms}ms")
+            .with_field("ms", 42u32)
+            .unwrap();
+        let mut encoded = encode_heapless::<128>(&(0u8, &log)).unwrap();
+        assert_eq!(encoded, encode(&(0u8, &log), &mut [0; 128]).unwrap());
+
+        let OwnedPackage::<4, 16>::Log(owned) = decode(&mut encoded).unwrap() else {
+            panic!("not a log package");
+        };
+        assert_eq!(owned.message, "booted in {ms}ms");
+
+        assert!(matches!(
+            encode_heapless::<8>(&(0u8, &log)),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        use crate::telemetry::{DataPoint, OwnedDataPoint, OwnedTelemetryFrame, TelemetryFrame};
+        use postcard::accumulator::{CobsAccumulator, FeedResult};
+
+        let mut first = TelemetryFrame::<2>::new();
+        first.insert("a", DataPoint::U32(1)).unwrap();
+        let mut second = TelemetryFrame::<2>::new();
+        second.insert("b", DataPoint::F32(2.0)).unwrap();
+        second.insert("c", DataPoint::I32(-3)).unwrap();
+
+        let mut buf = [0u8; 64];
+        let frames = [first, second];
+        let batch = encode_batch(&frames, &mut buf).unwrap();
+        assert_eq!(batch.iter().filter(|&&b| b == 0).count(), 2);
+
+        let mut accumulator = CobsAccumulator::<64>::new();
+        let mut decoded = heapless::Vec::<OwnedTelemetryFrame<2, 4>, 2>::new();
+        let mut window: &[u8] = batch;
+        while !window.is_empty() {
+            window = match accumulator.feed(window) {
+                FeedResult::Success { data, remaining } => {
+                    decoded.push(data).unwrap();
+                    remaining
+                }
+                _ => panic!("failed to decode batch"),
+            };
+        }
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0][&"a".into()], OwnedDataPoint::U32(1));
+        assert_eq!(decoded[1][&"c".into()], OwnedDataPoint::I32(-3));
+
+        assert!(matches!(
+            encode_batch(&frames, &mut [0; 8]),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    /// A COBS-framed `Package::Telemetry` with the entries `f: F32(1.0)`,
+    /// `i: I32(-2)`, and `u: U32(300)`, in that order. If this changes,
+    /// the wire format changed.
+    const GOLDEN_TELEMETRY: [u8; 20] = [
+        5, 1, 3, 1, 102, 1, 1, 12, 128, 63, 1, 105, 1, 3, 1, 117, 2, 172, 2, 0,
+    ];
+
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn test_golden_telemetry_encoding() {
+        let mut frame = crate::telemetry::TelemetryFrame::<4>::new();
+        frame.insert("f", 1.0f32.into()).unwrap();
+        frame.insert("i", (-2i32).into()).unwrap();
+        frame.insert("u", 300u32.into()).unwrap();
+        let package = Package::Telemetry(frame);
+
+        let mut buf = [0u8; 64];
+        assert_eq!(encode(&package, &mut buf).unwrap(), GOLDEN_TELEMETRY);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_golden_telemetry_decoding() {
+        use telemetry::DataPoint;
+
+        let mut buf = GOLDEN_TELEMETRY;
+        let Package::Telemetry(frame) = decode(&mut buf).unwrap() else {
+            panic!("not a telemetry package");
+        };
+        assert_eq!(frame.len(), 3);
+        assert_eq!(frame["f"], DataPoint::F32(1.0));
+        assert_eq!(frame["i"], DataPoint::I32(-2));
+        assert_eq!(frame["u"], DataPoint::U32(300));
+    }
+
+    #[test]
+    fn test_owned_package_decoding() {
+        use crate::telemetry::OwnedDataPoint;
+
+        let mut buf = GOLDEN_TELEMETRY;
+        let OwnedPackage::<4, 8>::Telemetry(frame) = decode(&mut buf).unwrap() else {
+            panic!("not a telemetry package");
+        };
+        assert_eq!(frame.len(), 3);
+        assert_eq!(frame[&"f".into()], OwnedDataPoint::F32(1.0));
+        assert_eq!(frame[&"i".into()], OwnedDataPoint::I32(-2));
+        assert_eq!(frame[&"u".into()], OwnedDataPoint::U32(300));
+
+        let mut buf = GOLDEN_TELEMETRY;
+        assert!(matches!(
+            decode::<OwnedPackage<2, 8>>(&mut buf),
+            Err(Error::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn test_owned_log_decoding() {
+        use crate::log::{Level, Log, OwnedLogParameter};
+
+        let log = Log::error("{sensor} failed")
+            .with_field("sensor", "imu")
+            .unwrap();
+        // Variant index 0 followed by the log is how `Package::Log` is
+        // encoded, and works with either version of `Package`.
+        let mut buf = [0u8; 64];
+        let encoded = encode(&(0u8, &log), &mut buf).unwrap();
+
+        let OwnedPackage::<4, 16>::Log(owned) = decode(encoded).unwrap() else {
+            panic!("not a log package");
+        };
+        assert!(matches!(owned.level, Level::Error));
+        assert_eq!(owned.message, "{sensor} failed");
+        assert_eq!(
+            owned.parameters[&"sensor".into()],
+            OwnedLogParameter::String("imu".into())
+        );
+    }
+
+    #[test]
+    fn test_transport_trait_object() {
+        use crate::telemetry::{OwnedDataPoint, OwnedTelemetryFrame};
+
+        let mut frame = OwnedTelemetryFrame::<4, 8>::new();
+        frame.insert("f".into(), OwnedDataPoint::F32(1.0)).unwrap();
+        frame
+            .insert("s".into(), OwnedDataPoint::Str("ok".into()))
+            .unwrap();
+
+        let transport: &dyn Transport<OwnedTelemetryFrame<4, 8>> = &PostcardCobs;
+        let mut buf = [0u8; 64];
+        let encoded = transport.encode(&frame, &mut buf).unwrap();
+        assert_eq!(transport.decode(encoded).unwrap(), frame);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut buf = [0u8; 64];
+        assert!(matches!(
+            encode(&[0u8; 32], &mut [0; 16]),
+            Err(Error::BufferTooSmall)
+        ));
+
+        // A telemetry package with one entry whose value is missing.
+        let encoded = encode(&[1u8, 1, 1, b'a'], &mut buf).unwrap();
+        assert!(matches!(
+            decode::<OwnedPackage<4, 8>>(encoded),
+            Err(Error::Truncated)
+        ));
+
+        // A telemetry package with an unknown data point variant.
+        let encoded = encode(&[1u8, 1, 1, b'a', 9, 0], &mut buf).unwrap();
+        assert!(matches!(
+            decode::<OwnedPackage<4, 8>>(encoded),
+            Err(Error::InvalidData)
+        ));
+
+        let encoded = encode(&[7u8; 8], &mut buf).unwrap();
+        assert_eq!(
+            decode_copy::<[u8; 8]>(encoded, &mut [0; 10]).unwrap(),
+            [7; 8]
+        );
+        assert!(matches!(
+            decode_copy::<[u8; 8]>(encoded, &mut [0; 9]),
+            Err(Error::DecodeBufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_decode_bounded_rejects_long_frames() {
+        let mut buf = [0u8; 64];
+        let len = encode(&[7u8; 32], &mut buf).unwrap().len();
+        assert!(decode_bounded::<[u8; 32]>(&mut buf.clone(), len).is_ok());
+        assert!(matches!(
+            decode_bounded::<[u8; 32]>(&mut buf, len - 1),
+            Err(Error::FrameTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_decode_crafted_length_prefix() {
+        // A telemetry package claiming u32::MAX entries, followed by a
+        // single one. Arrays encode without a length prefix, so this is
+        // framed as-is. The frame ends long before the claimed entries.
+        let payload = [1u8, 0xff, 0xff, 0xff, 0xff, 0x0f, 1, b'a', 2, 1];
+
+        let mut buf = [0u8; 32];
+        let encoded = encode(&payload, &mut buf).unwrap();
+        assert!(matches!(
+            decode::<OwnedPackage<4, 8>>(encoded),
+            Err(Error::Truncated)
+        ));
+
+        #[cfg(feature = "std")]
+        {
+            let encoded = encode(&payload, &mut buf).unwrap();
+            assert!(matches!(decode::<Package>(encoded), Err(Error::Truncated)));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod counting {
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+
+        /// Counts allocations made by the current thread, so tests
+        /// running in parallel do not interfere.
+        pub struct CountingAllocator;
+
+        thread_local! {
+            pub static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                unsafe { System.alloc(layout) }
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                unsafe { System.dealloc(ptr, layout) }
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_decoder_reuses_buffer() {
+        use crate::telemetry::{OwnedDataPoint, OwnedTelemetryFrame};
+        use counting::ALLOCATIONS;
+
+        let frames: Vec<Vec<u8>> = (0..100u32)
+            .map(|i| {
+                let mut frame = crate::telemetry::TelemetryFrame::<1>::new();
+                frame.insert("i", i.into()).unwrap();
+                encode(&frame, &mut [0; 16]).unwrap().to_vec()
+            })
+            .collect();
+
+        let mut decoder = Decoder::with_capacity(16);
+        let before = ALLOCATIONS.with(|count| count.get());
+        for (i, frame) in frames.iter().enumerate() {
+            let decoded: OwnedTelemetryFrame<1, 4> = decoder.decode(frame).unwrap();
+            assert_eq!(decoded[&"i".into()], OwnedDataPoint::U32(i as u32));
+        }
+        assert_eq!(ALLOCATIONS.with(|count| count.get()), before);
+        // The frames are untouched, so they decode again.
+        assert!(decoder
+            .decode::<OwnedTelemetryFrame<1, 4>>(&frames[0])
+            .is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_decode_oversized_frame() {
+        use counting::ALLOCATIONS;
+
+        // A well-formed log, at the lowest level and without
+        // parameters, whose message is longer than any frame the host
+        // accepts. Decoding it would allocate the whole message.
+        let message = "a".repeat(MAX_FRAME_LEN);
+        let mut buf = vec![0u8; 2 * MAX_FRAME_LEN];
+        let encoded = encode(&(0u8, 0u8, message.as_str(), 0u8), &mut buf)
+            .unwrap()
+            .to_vec();
+
+        let mut frame = encoded.clone();
+        let before = ALLOCATIONS.with(|count| count.get());
+        assert!(matches!(
+            decode::<Package>(&mut frame),
+            Err(Error::FrameTooLong)
+        ));
+        assert_eq!(ALLOCATIONS.with(|count| count.get()), before);
+
+        // Within the bound, the same log decodes.
+        let mut frame = encoded;
+        assert!(matches!(
+            decode_bounded::<Package>(&mut frame, usize::MAX),
+            Ok(Package::Log(_))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_decode_with_raw() {
+        let mut buf = GOLDEN_TELEMETRY;
+        let (package, raw) = decode_with_raw::<Package>(&mut buf).unwrap();
+        let Package::Telemetry(frame) = package else {
+            panic!("not a telemetry package");
+        };
+        assert_eq!(raw.len(), 18);
+
+        let Package::Telemetry(redecoded) = postcard::from_bytes(&raw).unwrap() else {
+            panic!("not a telemetry package");
+        };
+        assert_eq!(redecoded, frame);
+        // Framing the raw bytes again gives back the original frame.
+        let mut framed = [0u8; 32];
+        let len = cobs::encode(&raw, &mut framed);
+        assert_eq!(
+            framed[..len],
+            GOLDEN_TELEMETRY[..GOLDEN_TELEMETRY.len() - 1]
+        );
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_std_round_trip() {
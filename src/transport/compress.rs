@@ -0,0 +1,210 @@
+//! Run-length compression for slow links
+//!
+//! Frames are serialized with postcard, compressed with a simple
+//! PackBits-style run-length encoding, and only then wrapped in COBS.
+//! Compression is deterministic and lossless, and both directions
+//! work without `std`.
+//!
+//! The compressed stream is a sequence of chunks, each starting with
+//! a control byte `c`:
+//!
+//! - `c < 128`: `c + 1` literal bytes follow.
+//! - `c >= 128`: the following byte is repeated `c - 126` times.
+//!
+//! Runs are only worth it for frames with repeated bytes, such as
+//! zeroed floats. In the worst case the output is one byte larger per
+//! 128 input bytes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+const MAX_LITERAL: usize = 128;
+const MIN_RUN: usize = 3;
+const MAX_RUN: usize = 129;
+
+/// Compress `input` into `buf`, returning the used part of `buf`.
+pub fn compress<'b>(input: &[u8], buf: &'b mut [u8]) -> Result<&'b mut [u8], Error> {
+    let mut out = 0;
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let run = input[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&b| b == input[i])
+            .count();
+        if run >= MIN_RUN {
+            out = push_literals(&input[literal_start..i], buf, out)?;
+            let chunk = buf.get_mut(out..out + 2).ok_or(Error::BufferTooSmall)?;
+            chunk[0] = (run + 126) as u8;
+            chunk[1] = input[i];
+            out += 2;
+            i += run;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    out = push_literals(&input[literal_start..], buf, out)?;
+    Ok(&mut buf[..out])
+}
+
+/// Write literal chunks for `literals` into `buf` at offset `out`,
+/// returning the new offset.
+fn push_literals(literals: &[u8], buf: &mut [u8], mut out: usize) -> Result<usize, Error> {
+    for chunk in literals.chunks(MAX_LITERAL) {
+        let dest = buf
+            .get_mut(out..out + 1 + chunk.len())
+            .ok_or(Error::BufferTooSmall)?;
+        dest[0] = (chunk.len() - 1) as u8;
+        dest[1..].copy_from_slice(chunk);
+        out += dest.len();
+    }
+    Ok(out)
+}
+
+/// Decompress `input` into `buf`, returning the used part of `buf`.
+/// Fails with [`Error::Truncated`] if `input` ends within a chunk, and
+/// with [`Error::DecodeBufferTooSmall`] if `buf` is too small.
+pub fn decompress<'b>(input: &[u8], buf: &'b mut [u8]) -> Result<&'b mut [u8], Error> {
+    let mut out = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let control = input[i] as usize;
+        if control < MAX_LITERAL {
+            let len = control + 1;
+            let literals = input.get(i + 1..i + 1 + len).ok_or(Error::Truncated)?;
+            buf.get_mut(out..out + len)
+                .ok_or(Error::DecodeBufferTooSmall)?
+                .copy_from_slice(literals);
+            out += len;
+            i += 1 + len;
+        } else {
+            let len = control - 126;
+            let byte = *input.get(i + 1).ok_or(Error::Truncated)?;
+            buf.get_mut(out..out + len)
+                .ok_or(Error::DecodeBufferTooSmall)?
+                .fill(byte);
+            out += len;
+            i += 2;
+        }
+    }
+    Ok(&mut buf[..out])
+}
+
+/// Serialize, compress, and COBS-frame an item for transmission.
+///
+/// `scratch` holds the intermediate serialized and compressed bytes,
+/// so it must be large enough for both.
+pub fn encode<'b>(
+    item: &impl Serialize,
+    scratch: &mut [u8],
+    buf: &'b mut [u8],
+) -> Result<&'b mut [u8], Error> {
+    let len = postcard::to_slice(item, scratch)
+        .map_err(|_| Error::BufferTooSmall)?
+        .len();
+    let (serialized, rest) = scratch.split_at_mut(len);
+    let compressed = compress(serialized, rest)?;
+    let encoded = cobs::try_encode(compressed, buf).map_err(|_| Error::BufferTooSmall)?;
+    *buf.get_mut(encoded).ok_or(Error::BufferTooSmall)? = 0;
+    Ok(&mut buf[..encoded + 1])
+}
+
+/// Decode a COBS-framed, compressed item.
+///
+/// The frame in `buf` is decoded in place, and `scratch` receives the
+/// decompressed serialized bytes that the item may borrow from.
+pub fn decode<'a, T>(buf: &mut [u8], scratch: &'a mut [u8]) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let len = cobs::decode_in_place(buf).map_err(|_| Error::InvalidData)?;
+    let decompressed = decompress(&buf[..len], scratch)?;
+    postcard::from_bytes(decompressed).map_err(super::decode_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::telemetry::{OwnedDataPoint, OwnedTelemetryFrame, TelemetryReporter};
+
+    #[test]
+    fn test_compress_round_trip() {
+        let input = [1, 2, 2, 2, 3, 0, 0, 0, 0, 0, 0, 4, 5, 6, 6];
+        let mut compressed = [0u8; 32];
+        let compressed = compress(&input, &mut compressed).unwrap();
+        let mut decompressed = [0u8; 32];
+        assert_eq!(decompress(compressed, &mut decompressed).unwrap(), &input);
+    }
+
+    #[test]
+    fn test_compress_long_runs_and_literals() {
+        let mut input = [0u8; 600];
+        for (i, b) in input.iter_mut().enumerate().skip(300) {
+            *b = i as u8;
+        }
+        let mut compressed = [0u8; 700];
+        let compressed = compress(&input, &mut compressed).unwrap();
+        let mut decompressed = [0u8; 600];
+        assert_eq!(decompress(compressed, &mut decompressed).unwrap(), &input);
+    }
+
+    #[test]
+    fn test_decompress_truncated() {
+        let mut buf = [0u8; 8];
+        assert!(matches!(
+            decompress(&[3, 1, 2], &mut buf),
+            Err(Error::Truncated)
+        ));
+        assert!(matches!(
+            decompress(&[130], &mut buf),
+            Err(Error::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_decode_scratch_too_small() {
+        let mut buf = [0u8; 64];
+        let encoded = encode(&[0u8; 32], &mut [0; 64], &mut buf).unwrap();
+        assert!(matches!(
+            decode::<[u8; 32]>(encoded, &mut [0; 16]),
+            Err(Error::DecodeBufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let mut reporter = TelemetryReporter::<4>::new();
+        reporter.record("a", 0.0f32).unwrap();
+        reporter.record("b", 0.0f32).unwrap();
+        reporter.record("c", 7u32).unwrap();
+        reporter.record("d", "idle").unwrap();
+        let frame = reporter.report();
+
+        let mut buf = [0u8; 64];
+        let encoded = encode(&frame, &mut [0; 64], &mut buf).unwrap();
+        let mut scratch = [0u8; 64];
+        let decoded: OwnedTelemetryFrame<4, 8> = decode(encoded, &mut scratch).unwrap();
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded[&"a".into()], OwnedDataPoint::F32(0.0));
+        assert_eq!(decoded[&"b".into()], OwnedDataPoint::F32(0.0));
+        assert_eq!(decoded[&"c".into()], OwnedDataPoint::U32(7));
+        assert_eq!(decoded[&"d".into()], OwnedDataPoint::Str("idle".into()));
+    }
+
+    #[test]
+    fn test_reduces_repetitive_frame() {
+        let mut reporter = TelemetryReporter::<8>::new();
+        for key in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            reporter.record(key, 0.0f32).unwrap();
+        }
+        let frame = reporter.report();
+
+        let plain = super::super::encode(&frame, &mut [0; 128]).unwrap().len();
+        let compressed = encode(&frame, &mut [0; 128], &mut [0; 128]).unwrap().len();
+        assert!(compressed < plain, "{compressed} >= {plain}");
+    }
+}
@@ -0,0 +1,113 @@
+//! Event channel for live dashboards
+//!
+//! Decoded [`Package`]s are dispatched as typed [`DashboardEvent`]s
+//! into an [`std::sync::mpsc`] channel, each stamped with the time it
+//! was received, so that a UI thread can consume them in order.
+//!
+//! ```
+//! # use std::sync::mpsc;
+//! # use postcard_telemetry::transport::{dashboard, Package};
+//! let (sender, receiver) = mpsc::channel();
+//! let packages: Vec<Package> = Vec::new(); // Decoded from a link
+//! std::thread::spawn(move || dashboard::dispatch(packages, &sender));
+//! for event in receiver {
+//!     // Update the UI
+//! }
+//! ```
+
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+use super::log::Log;
+use super::telemetry::{TelemetryFrame, TimestampedFrame};
+use super::Package;
+
+/// An update for a live dashboard.
+#[derive(Debug, Clone)]
+pub struct DashboardEvent {
+    /// When the package was dispatched.
+    pub received: Instant,
+    pub kind: DashboardEventKind,
+}
+
+/// The content of a [`DashboardEvent`].
+#[derive(Debug, Clone)]
+pub enum DashboardEventKind {
+    Log(Log),
+    Telemetry(TelemetryFrame),
+    TimestampedTelemetry(TimestampedFrame),
+}
+
+/// Dispatch a stream of packages into a channel as dashboard events,
+/// in order. Transport-level packages such as acks are skipped.
+///
+/// Returns the number of events sent. Dispatching stops early if the
+/// receiving end of the channel has been dropped.
+pub fn dispatch(
+    packages: impl IntoIterator<Item = Package>,
+    sender: &Sender<DashboardEvent>,
+) -> usize {
+    let mut sent = 0;
+    for package in packages {
+        let kind = match package {
+            Package::Log(log) => DashboardEventKind::Log(log),
+            Package::Telemetry(frame) => DashboardEventKind::Telemetry(frame),
+            Package::TimestampedTelemetry(frame) => DashboardEventKind::TimestampedTelemetry(frame),
+            Package::Ack(_) => continue,
+        };
+        let event = DashboardEvent {
+            received: Instant::now(),
+            kind,
+        };
+        if sender.send(event).is_err() {
+            break;
+        }
+        sent += 1;
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    use crate::log as embedded;
+    use crate::transport::reliability::Ack;
+    use crate::transport::telemetry::DataPoint;
+
+    #[test]
+    fn test_dispatch_in_order() {
+        let log = Log::from(embedded::Log::info("hello"));
+        let frame = TelemetryFrame::from([("g".to_string(), DataPoint::F32(9.81))]);
+        let packages = vec![
+            Package::Telemetry(frame.clone()),
+            Package::Ack(Ack { seq: 0 }),
+            Package::Log(log.clone()),
+            Package::Telemetry(frame.clone()),
+        ];
+
+        let (sender, receiver) = mpsc::channel();
+        assert_eq!(dispatch(packages, &sender), 3);
+        drop(sender);
+
+        let events: Vec<_> = receiver.iter().collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0].kind, DashboardEventKind::Telemetry(f) if *f == frame));
+        assert!(
+            matches!(&events[1].kind, DashboardEventKind::Log(l) if l.to_string() == log.to_string())
+        );
+        assert!(matches!(&events[2].kind, DashboardEventKind::Telemetry(f) if *f == frame));
+        assert!(events[0].received <= events[1].received);
+        assert!(events[1].received <= events[2].received);
+    }
+
+    #[test]
+    fn test_dispatch_stops_when_receiver_dropped() {
+        let (sender, receiver) = mpsc::channel();
+        drop(receiver);
+        let packages = vec![Package::Telemetry(TelemetryFrame::new())];
+        assert_eq!(dispatch(packages, &sender), 0);
+    }
+}
@@ -7,6 +7,8 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::log as embedded;
+
 /// A log message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Log {
@@ -15,32 +17,46 @@ pub struct Log {
     parameters: HashMap<String, LogParameter>,
 }
 
+impl<K: Eq + AsRef<str>> From<embedded::Log<K>> for Log {
+    fn from(log: embedded::Log<K>) -> Self {
+        Self {
+            level: log.level.into(),
+            message: log.message.to_string(),
+            parameters: log
+                .parameters
+                .iter()
+                .map(|(name, parameter)| (name.as_ref().to_string(), parameter.clone().into()))
+                .collect(),
+        }
+    }
+}
+
+impl Log {
+    /// The level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The message, with placeholders for the parameters.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The parameters of the message, by name.
+    pub fn parameters(&self) -> &HashMap<String, LogParameter> {
+        &self.parameters
+    }
+}
+
 impl std::fmt::Display for Log {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut message = self.message.to_string();
-        let mut parameters = self.parameters.iter();
-        while let Some((name, parameter)) = parameters.next() {
-            match parameter {
-                LogParameter::String(s) => {
-                    message = message.replace(&format!("{{{}}}", name), s);
-                }
-                LogParameter::Float(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
-                }
-                LogParameter::Integer(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
-                }
-                LogParameter::UnsignedInteger(v) => {
-                    message = message.replace(&format!("{{{}}}", name), &format!("{}", v));
-                }
-            }
-        }
-        write!(f, "[{}] {}", self.level, message)
+        write!(f, "[{}] ", self.level)?;
+        embedded::write_message(f, &self.message, |name| self.parameters.get(name))
     }
 }
 
-/// A log message level.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// A log message level, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Level {
     Debug,
     Info,
@@ -48,6 +64,17 @@ pub enum Level {
     Error,
 }
 
+impl From<embedded::Level> for Level {
+    fn from(level: embedded::Level) -> Self {
+        match level {
+            embedded::Level::Debug => Level::Debug,
+            embedded::Level::Info => Level::Info,
+            embedded::Level::Warning => Level::Warning,
+            embedded::Level::Error => Level::Error,
+        }
+    }
+}
+
 impl std::fmt::Display for Level {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -68,6 +95,17 @@ pub enum LogParameter {
     UnsignedInteger(u32),
 }
 
+impl From<embedded::LogParameter> for LogParameter {
+    fn from(parameter: embedded::LogParameter) -> Self {
+        match parameter {
+            embedded::LogParameter::String(s) => LogParameter::String(s.to_string()),
+            embedded::LogParameter::Float(v) => LogParameter::Float(v),
+            embedded::LogParameter::Integer(v) => LogParameter::Integer(v),
+            embedded::LogParameter::UnsignedInteger(v) => LogParameter::UnsignedInteger(v),
+        }
+    }
+}
+
 impl std::fmt::Display for LogParameter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -84,7 +122,6 @@ mod tests {
     use super::*;
 
     use super::super::{decode, encode};
-    use crate::log as embedded;
 
     #[test]
     fn test_can_decode_embedded_version() {
@@ -92,8 +129,64 @@ mod tests {
             .with_field("name", "Bob")
             .unwrap();
         let decoded = encode(&embedded_log, &mut [0; 128])
-            .and_then(|buf| decode::<Log>(buf))
+            .and_then(decode::<Log>)
             .unwrap();
         assert_eq!(format!("{embedded_log}"), format!("{decoded}"));
     }
+
+    #[test]
+    fn test_from_embedded_version() {
+        let embedded_log = embedded::Log::warning("{count} {unit} left at {level}")
+            .with_field("count", 3u32)
+            .unwrap()
+            .with_field("unit", "cells")
+            .unwrap()
+            .with_field("level", 0.25f32)
+            .unwrap();
+        let converted = Log::from(embedded_log.clone());
+        assert_eq!(format!("{embedded_log}"), format!("{converted}"));
+        assert_eq!(converted.parameters.len(), 3);
+    }
+
+    #[test]
+    fn test_display_defaults() {
+        let embedded_log = embedded::Log::error("{motor:-all motors} stalled at {rpm:-0} rpm")
+            .with_field("rpm", 1200u32)
+            .unwrap();
+        let decoded = encode(&embedded_log, &mut [0; 128])
+            .and_then(decode::<Log>)
+            .unwrap();
+        assert_eq!(
+            format!("{decoded}"),
+            "[ERROR] all motors stalled at 1200 rpm"
+        );
+    }
+
+    #[test]
+    fn test_group_by_level() {
+        let logs = [
+            embedded::Log::info("a"),
+            embedded::Log::error("b"),
+            embedded::Log::info("c"),
+            embedded::Log::warning("d"),
+        ]
+        .map(Log::from);
+        let mut counts = HashMap::new();
+        for log in &logs {
+            *counts.entry(log.level()).or_insert(0) += 1;
+        }
+        assert_eq!(
+            counts,
+            HashMap::from([(Level::Info, 2), (Level::Warning, 1), (Level::Error, 1)])
+        );
+
+        let severe: Vec<_> = logs
+            .iter()
+            .filter(|log| log.level() >= Level::Warning)
+            .map(Log::message)
+            .collect();
+        assert_eq!(severe, ["b", "d"]);
+        assert!(logs.iter().all(|log| log.parameters().is_empty()));
+        assert!(Level::Debug < Level::Warning);
+    }
 }
@@ -0,0 +1,329 @@
+//! At-least-once delivery on top of the transport
+//!
+//! Raw COBS frames can be dropped silently. For messages that must
+//! arrive, wrap them in a [`Sequenced`] envelope and keep them in a
+//! [`RetransmitQueue`] until the receiver answers with an [`Ack`]
+//! carrying the same sequence number. Frames that are not acknowledged
+//! within the timeout are handed out again for retransmission.
+//!
+//! ```
+//! # use postcard_telemetry::transport::reliability::{Ack, RetransmitQueue};
+//! # fn main() -> Result<(), postcard_telemetry::error::Error> {
+//! let mut queue = RetransmitQueue::<&str, 4>::new(100);
+//! let frame = queue.send("arm", 0)?;
+//! // Encode and send frame, later:
+//! assert!(queue.poll_retransmit(150).is_some());
+//! queue.ack(Ack { seq: frame.seq });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Timestamps are ticks of a wrapping `u32` counter, see
+//! [`crate::telemetry::tick_delta`]. Receivers may see duplicates and
+//! should deduplicate by sequence number. Use [`Backoff`] to space out
+//! retries on a congested link, and [`LossTracker`] to count frames
+//! lost on the way.
+
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::rng::XorShift32;
+use crate::telemetry::tick_delta;
+
+/// A payload tagged with a sequence number.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sequenced<T> {
+    pub seq: u32,
+    pub payload: T,
+}
+
+/// An acknowledgement of a received [`Sequenced`] frame.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Ack {
+    pub seq: u32,
+}
+
+impl<T> Sequenced<T> {
+    /// The acknowledgement a receiver should send for this frame.
+    pub fn ack(&self) -> Ack {
+        Ack { seq: self.seq }
+    }
+}
+
+/// Tracks up to `N` unacknowledged frames and hands them out again
+/// when they time out.
+pub struct RetransmitQueue<T, const N: usize> {
+    timeout: u32,
+    next_seq: u32,
+    outstanding: Vec<(u32, Sequenced<T>), N>,
+}
+
+impl<T: Clone, const N: usize> RetransmitQueue<T, N> {
+    /// Create a new queue retransmitting after `timeout` ticks.
+    pub const fn new(timeout: u32) -> Self {
+        Self {
+            timeout,
+            next_seq: 0,
+            outstanding: Vec::new(),
+        }
+    }
+
+    /// Assign the next sequence number to `payload` and track it as
+    /// sent at `now`. Returns the frame to transmit, or
+    /// [`Error::Saturated`] if `N` frames are already outstanding.
+    pub fn send(&mut self, payload: T, now: u32) -> Result<Sequenced<T>, Error> {
+        let frame = Sequenced {
+            seq: self.next_seq,
+            payload,
+        };
+        self.outstanding
+            .push((now, frame.clone()))
+            .map_err(|_| Error::Saturated)?;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        Ok(frame)
+    }
+
+    /// Stop tracking the acknowledged frame. Returns `false` if no
+    /// such frame was outstanding, e.g. for a duplicate ack.
+    pub fn ack(&mut self, ack: Ack) -> bool {
+        match self
+            .outstanding
+            .iter()
+            .position(|(_, frame)| frame.seq == ack.seq)
+        {
+            Some(index) => {
+                self.outstanding.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the oldest frame that has not been acknowledged within
+    /// the timeout, restarting its timer at `now`. Call repeatedly
+    /// until it returns `None` to drain all timed out frames.
+    pub fn poll_retransmit(&mut self, now: u32) -> Option<Sequenced<T>> {
+        let timeout = self.timeout;
+        self.outstanding
+            .iter_mut()
+            .find(|(sent_at, _)| tick_delta(*sent_at, now) >= timeout)
+            .map(|(sent_at, frame)| {
+                *sent_at = now;
+                frame.clone()
+            })
+    }
+
+    /// The number of unacknowledged frames.
+    pub fn outstanding(&self) -> usize {
+        self.outstanding.len()
+    }
+}
+
+/// The number of frames missing between two received sequence
+/// numbers, handling wraparound. Returns zero if `next` directly
+/// follows `prev`, repeats it, or arrived out of order, i.e. lies up
+/// to half the sequence space behind it.
+pub fn seq_gap(prev: u32, next: u32) -> u32 {
+    match next.wrapping_sub(prev) {
+        0 => 0,
+        delta if delta > i32::MAX as u32 => 0,
+        delta => delta - 1,
+    }
+}
+
+/// Counts frames lost on a link from the sequence numbers of the
+/// frames that did arrive, see [`seq_gap`].
+///
+/// A frame arriving out of order is assumed to be one that was
+/// counted as lost earlier, and is taken off the loss count again.
+/// Deduplicate frames before observing them if the link can repeat
+/// frames.
+#[derive(Debug, Clone, Default)]
+pub struct LossTracker {
+    last: Option<u32>,
+    lost: u64,
+    reordered: u64,
+}
+
+impl LossTracker {
+    /// Create a tracker that has not seen any frames.
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            lost: 0,
+            reordered: 0,
+        }
+    }
+
+    /// Record the sequence number of a received frame.
+    pub fn observe(&mut self, seq: u32) {
+        let Some(last) = self.last else {
+            self.last = Some(seq);
+            return;
+        };
+        match seq.wrapping_sub(last) {
+            0 => {}
+            delta if delta > i32::MAX as u32 => {
+                self.lost = self.lost.saturating_sub(1);
+                self.reordered += 1;
+            }
+            _ => {
+                self.lost += seq_gap(last, seq) as u64;
+                self.last = Some(seq);
+            }
+        }
+    }
+
+    /// The number of frames lost so far.
+    pub fn lost(&self) -> u64 {
+        self.lost
+    }
+
+    /// The number of frames that arrived out of order so far.
+    pub fn reordered(&self) -> u64 {
+        self.reordered
+    }
+}
+
+/// Capped exponential backoff with jitter, for spacing out
+/// retransmissions.
+///
+/// The `n`th delay is drawn from the upper half of
+/// `min(base * 2^n, cap)` ticks, so that senders that failed at the
+/// same time spread out. Jitter comes from a small deterministic
+/// generator seeded at construction.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: u32,
+    cap: u32,
+    attempt: u32,
+    rng: XorShift32,
+}
+
+impl Backoff {
+    /// Create a backoff starting at `base` ticks and growing up to
+    /// `cap` ticks, with jitter seeded by `seed`.
+    pub const fn new(base: u32, cap: u32, seed: u32) -> Self {
+        Self {
+            base,
+            cap,
+            attempt: 0,
+            rng: XorShift32::new(seed),
+        }
+    }
+
+    /// The delay before the next attempt, in ticks.
+    pub fn next_delay(&mut self) -> u32 {
+        let ceiling = self
+            .base
+            .checked_shl(self.attempt)
+            .filter(|delay| delay >> self.attempt == self.base)
+            .map_or(self.cap, |delay| delay.min(self.cap));
+        self.attempt = self.attempt.saturating_add(1);
+
+        let half = ceiling / 2;
+        half + self.rng.next_u32() % (ceiling - half + 1)
+    }
+
+    /// Start over from the base delay, e.g. after a successful
+    /// delivery.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seq_gap() {
+        assert_eq!(seq_gap(4, 5), 0);
+        assert_eq!(seq_gap(4, 8), 3);
+        assert_eq!(seq_gap(u32::MAX - 1, 2), 3);
+        assert_eq!(seq_gap(5, 5), 0);
+        assert_eq!(seq_gap(5, 3), 0);
+        assert_eq!(seq_gap(1, u32::MAX), 0);
+    }
+
+    #[test]
+    fn test_loss_tracker() {
+        let mut tracker = LossTracker::new();
+        for seq in [u32::MAX - 2, u32::MAX - 1, 1, 2, 5] {
+            tracker.observe(seq);
+        }
+        assert_eq!(tracker.lost(), 4);
+        assert_eq!(tracker.reordered(), 0);
+
+        // 0 and 3 show up late, 5 is a duplicate.
+        for seq in [0, 3, 5, 6] {
+            tracker.observe(seq);
+        }
+        assert_eq!(tracker.lost(), 2);
+        assert_eq!(tracker.reordered(), 2);
+    }
+
+    #[test]
+    fn test_retransmits_dropped_frame() -> Result<(), Error> {
+        let mut queue = RetransmitQueue::<u32, 4>::new(10);
+        let first = queue.send(1, 0)?;
+        let second = queue.send(2, 1)?;
+        assert_eq!(second.seq, first.seq + 1);
+
+        // The first frame arrives, the second is dropped.
+        assert!(queue.ack(first.ack()));
+        assert!(queue.poll_retransmit(5).is_none());
+
+        let retransmitted = queue.poll_retransmit(11).unwrap();
+        assert_eq!(retransmitted, second);
+        // The timer restarted, so it is not handed out again yet.
+        assert!(queue.poll_retransmit(12).is_none());
+
+        assert!(queue.ack(retransmitted.ack()));
+        assert!(!queue.ack(retransmitted.ack()));
+        assert_eq!(queue.outstanding(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_saturated() -> Result<(), Error> {
+        let mut queue = RetransmitQueue::<u32, 1>::new(10);
+        queue.send(1, 0)?;
+        assert!(matches!(queue.send(2, 0), Err(Error::Saturated)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout_across_tick_wrap() -> Result<(), Error> {
+        let mut queue = RetransmitQueue::<u32, 1>::new(10);
+        queue.send(1, u32::MAX - 2)?;
+        assert!(queue.poll_retransmit(3).is_none());
+        assert!(queue.poll_retransmit(8).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backoff_grows_to_cap() {
+        let mut backoff = Backoff::new(10, 1000, 42);
+        let mut ceiling = 10;
+        for _ in 0..40 {
+            let delay = backoff.next_delay();
+            assert!((ceiling / 2..=ceiling).contains(&delay), "{delay}");
+            ceiling = (ceiling * 2).min(1000);
+        }
+
+        backoff.reset();
+        assert!(backoff.next_delay() <= 10);
+    }
+
+    #[test]
+    fn test_backoff_is_deterministic() {
+        let mut a = Backoff::new(1, u32::MAX, 7);
+        let mut b = Backoff::new(1, u32::MAX, 7);
+        for _ in 0..40 {
+            assert_eq!(a.next_delay(), b.next_delay());
+        }
+        assert!(a.next_delay() >= u32::MAX / 2);
+    }
+}
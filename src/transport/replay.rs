@@ -0,0 +1,234 @@
+//! Replaying recorded sessions on hosts
+//!
+//! Logs and telemetry are usually recorded as separate streams. The
+//! helpers here combine them back into a single timeline, and replay
+//! recorded sessions at their original pace.
+
+use std::time::Duration;
+
+use super::log::Log;
+use super::telemetry::TelemetryFrame;
+use super::{decode, frame_len, Package};
+use crate::error::Error;
+use crate::telemetry::tick_delta;
+
+/// A timestamped log message or telemetry frame.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A log message and its timestamp.
+    Log(u32, Log),
+    /// A telemetry frame and its timestamp.
+    Telemetry(u32, TelemetryFrame),
+}
+
+impl Event {
+    /// The timestamp of the event.
+    pub fn timestamp(&self) -> u32 {
+        match self {
+            Event::Log(timestamp, _) | Event::Telemetry(timestamp, _) => *timestamp,
+        }
+    }
+}
+
+/// Merge timestamped logs and telemetry frames into one list of
+/// events, sorted by timestamp.
+///
+/// The sort is stable: events from the same stream keep their order,
+/// and logs come before frames with the same timestamp. Timestamps
+/// are compared as plain integers, so streams that span a wrap of the
+/// tick counter need to be unwrapped first.
+pub fn merge_events(logs: &[(u32, Log)], frames: &[(u32, TelemetryFrame)]) -> Vec<Event> {
+    let mut events: Vec<Event> = logs
+        .iter()
+        .map(|(timestamp, log)| Event::Log(*timestamp, log.clone()))
+        .chain(
+            frames
+                .iter()
+                .map(|(timestamp, frame)| Event::Telemetry(*timestamp, frame.clone())),
+        )
+        .collect();
+    events.sort_by_key(Event::timestamp);
+    events
+}
+
+/// A source of delays for a [`Replayer`].
+pub trait Clock {
+    /// Wait for `duration`.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// The wall clock, sleeping the current thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Replays a recorded session, yielding each package after a delay
+/// matching the spacing of the recorded timestamps.
+///
+/// The recording is a sequence of COBS frames, each holding a
+/// `(timestamp, package)` tuple as produced by
+/// `encode(&(now, &package), buf)`. Timestamps are ticks of a wrapping
+/// `u32` counter, and `speed` scales the pace, so that `2.0` replays
+/// twice as fast.
+///
+/// ```no_run
+/// # use postcard_telemetry::transport::replay::{Replayer, SystemClock};
+/// let recording = std::fs::read("session.bin").unwrap();
+/// for package in Replayer::new(recording, 1000, 1.0, SystemClock).unwrap() {
+///     // Feed the package to the ground station
+/// }
+/// ```
+pub struct Replayer<C> {
+    recording: Vec<u8>,
+    position: usize,
+    ticks_per_second: u32,
+    speed: f32,
+    clock: C,
+    previous: Option<u32>,
+}
+
+impl<C: Clock> Replayer<C> {
+    /// Create a replayer for a recording with timestamps at
+    /// `ticks_per_second`. Will return [`Error::OutOfRange`] if
+    /// `ticks_per_second` is zero, or `speed` is not a positive,
+    /// finite number, or so small that the longest possible delay does
+    /// not fit into a [`Duration`].
+    pub fn new(
+        recording: Vec<u8>,
+        ticks_per_second: u32,
+        speed: f32,
+        clock: C,
+    ) -> Result<Self, Error> {
+        if ticks_per_second == 0 || !(speed > 0.0 && speed.is_finite()) {
+            return Err(Error::OutOfRange);
+        }
+        let longest = u32::MAX as f64 / ticks_per_second as f64 / speed as f64;
+        if Duration::try_from_secs_f64(longest).is_err() {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Self {
+            recording,
+            position: 0,
+            ticks_per_second,
+            speed,
+            clock,
+            previous: None,
+        })
+    }
+
+    /// The clock, e.g. to inspect a mock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+}
+
+impl<C: Clock> Iterator for Replayer<C> {
+    type Item = Result<(u32, Package), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &mut self.recording[self.position..];
+        if rest.is_empty() {
+            return None;
+        }
+        let len = frame_len(rest);
+        self.position += len;
+        let (timestamp, package): (u32, Package) = match decode(&mut rest[..len]) {
+            Ok(item) => item,
+            Err(e) => return Some(Err(e)),
+        };
+
+        if let Some(previous) = self.previous {
+            let ticks = tick_delta(previous, timestamp) as f64;
+            let seconds = ticks / self.ticks_per_second as f64 / self.speed as f64;
+            self.clock.sleep(Duration::from_secs_f64(seconds));
+        }
+        self.previous = Some(timestamp);
+        Some(Ok((timestamp, package)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::log as embedded;
+
+    #[test]
+    fn test_merge_events() {
+        let logs = [
+            (5, embedded::Log::info("armed").into()),
+            (20, embedded::Log::warning("low battery").into()),
+        ];
+        let frames = [
+            (0, TelemetryFrame::new()),
+            (10, TelemetryFrame::new()),
+            (20, TelemetryFrame::new()),
+        ];
+        let events = merge_events(&logs, &frames);
+
+        let order: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::Log(timestamp, _) => ('l', *timestamp),
+                Event::Telemetry(timestamp, _) => ('t', *timestamp),
+            })
+            .collect();
+        assert_eq!(order, [('t', 0), ('l', 5), ('t', 10), ('l', 20), ('t', 20)]);
+    }
+
+    #[derive(Default)]
+    struct MockClock {
+        sleeps: Vec<Duration>,
+    }
+
+    impl Clock for MockClock {
+        fn sleep(&mut self, duration: Duration) {
+            self.sleeps.push(duration);
+        }
+    }
+
+    #[test]
+    fn test_replayer_timing() {
+        use crate::transport::encode;
+        use crate::transport::reliability::Ack;
+
+        let mut recording = Vec::new();
+        for (timestamp, seq) in [(1000u32, 0), (1500, 1), (1600, 2)] {
+            let package = Package::Ack(Ack { seq });
+            recording.extend_from_slice(encode(&(timestamp, &package), &mut [0; 16]).unwrap());
+        }
+
+        let mut replayer = Replayer::new(recording, 1000, 2.0, MockClock::default()).unwrap();
+        let mut seqs = Vec::new();
+        for item in replayer.by_ref() {
+            let (_, Package::Ack(ack)) = item.unwrap() else {
+                panic!("not an ack");
+            };
+            seqs.push(ack.seq);
+        }
+        assert_eq!(seqs, [0, 1, 2]);
+        assert_eq!(
+            replayer.clock().sleeps,
+            [Duration::from_millis(250), Duration::from_millis(50)]
+        );
+    }
+
+    #[test]
+    fn test_replayer_rejects_invalid_pace() {
+        for (ticks_per_second, speed) in [
+            (1000, 0.0),
+            (1000, -1.0),
+            (1000, f32::NAN),
+            (1000, f32::MIN_POSITIVE),
+            (0, 1.0),
+        ] {
+            let replayer = Replayer::new(Vec::new(), ticks_per_second, speed, MockClock::default());
+            assert!(matches!(replayer, Err(Error::OutOfRange)));
+        }
+    }
+}
@@ -3,19 +3,433 @@
 //! These are mirrors of the embedded versions, but use owned data
 //! structures for easier decoding.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::Error;
+use crate::telemetry as embedded;
+
 /// A telemetry frame.
 pub type TelemetryFrame = HashMap<String, DataPoint>;
 
+/// A telemetry frame that keeps the entries in the order the device
+/// sent them, which is the order they were first recorded in, for
+/// display in the order the firmware intended.
+///
+/// Postcard encodes maps as sequences of pairs, so this decodes from
+/// the same bytes as [`TelemetryFrame`].
+pub type OrderedFrame = Vec<(String, DataPoint)>;
+
+/// A telemetry frame with keys replaced by ids, as sent by
+/// [`embedded::InternedFrame`]. See [`KeyDictionary::resolve`].
+pub type InternedFrame = HashMap<u16, DataPoint>;
+
+/// The keys of an [`embedded::KeyTable`], indexed by id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct KeyDictionary {
+    /// The registered keys, the key with id `i` at index `i`.
+    pub keys: Vec<String>,
+}
+
+impl KeyDictionary {
+    /// Replace the ids of an interned frame by their keys. Will return
+    /// [`Error::UnknownKey`] for ids missing from the dictionary.
+    pub fn resolve(&self, frame: InternedFrame) -> Result<TelemetryFrame, Error> {
+        frame
+            .into_iter()
+            .map(|(id, value)| {
+                let key = self.keys.get(id as usize).ok_or(Error::UnknownKey)?;
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// A telemetry frame with a timestamp for every data point.
+pub type TimestampedFrame = HashMap<String, (u32, DataPoint)>;
+
 /// A single data point.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DataPoint {
     F32(f32),
     I32(i32),
     U32(u32),
+    Str(String),
+    /// A fraction in `[0, 1]`, see [`DataPoint::fraction`].
+    Normalized(u16),
+}
+
+impl DataPoint {
+    /// The type of this data point.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            DataPoint::F32(_) => DataType::F32,
+            DataPoint::I32(_) => DataType::I32,
+            DataPoint::U32(_) => DataType::U32,
+            DataPoint::Str(_) => DataType::Str,
+            DataPoint::Normalized(_) => DataType::Normalized,
+        }
+    }
+
+    /// The value of a numeric data point as an `f64`, or `None` for
+    /// strings. Normalized data points are converted to fractions.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataPoint::F32(v) => Some(*v as f64),
+            DataPoint::I32(v) => Some(*v as f64),
+            DataPoint::U32(v) => Some(*v as f64),
+            DataPoint::Str(_) => None,
+            DataPoint::Normalized(v) => Some(embedded::denormalize(*v) as f64),
+        }
+    }
+
+    /// The value of a [`DataPoint::Normalized`] data point as a
+    /// fraction in `[0, 1]`.
+    pub fn fraction(&self) -> Option<f32> {
+        match self {
+            DataPoint::Normalized(v) => Some(embedded::denormalize(*v)),
+            _ => None,
+        }
+    }
+}
+
+impl From<embedded::DataPoint> for DataPoint {
+    fn from(value: embedded::DataPoint) -> Self {
+        match value {
+            embedded::DataPoint::F32(v) => DataPoint::F32(v),
+            embedded::DataPoint::I32(v) => DataPoint::I32(v),
+            embedded::DataPoint::U32(v) => DataPoint::U32(v),
+            embedded::DataPoint::Str(v) => DataPoint::Str(v.to_string()),
+            embedded::DataPoint::Normalized(v) => DataPoint::Normalized(v),
+        }
+    }
+}
+
+/// Convert an embedded telemetry frame into an owned host frame
+/// without a serialization round trip.
+pub fn from_embedded<const N: usize>(frame: &embedded::TelemetryFrame<N>) -> TelemetryFrame {
+    frame
+        .iter()
+        .map(|(name, value)| (name.to_string(), (*value).into()))
+        .collect()
+}
+
+/// A telemetry frame with floats encoded as scaled integers, as sent
+/// by [`embedded::ScaledFrame`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScaledFrame {
+    /// The number of integer steps per unit.
+    pub scale: u32,
+    /// The data points, with floats as multiples of `1 / scale`.
+    pub frame: HashMap<String, ScaledDataPoint>,
+}
+
+impl ScaledFrame {
+    /// Convert the scaled integers back into floats.
+    pub fn unscale(self) -> TelemetryFrame {
+        let scale = self.scale as f64;
+        self.frame
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    ScaledDataPoint::F32(v) => DataPoint::F32((v as f64 / scale) as f32),
+                    ScaledDataPoint::I32(v) => DataPoint::I32(v),
+                    ScaledDataPoint::U32(v) => DataPoint::U32(v),
+                    ScaledDataPoint::Str(v) => DataPoint::Str(v),
+                    ScaledDataPoint::Normalized(v) => DataPoint::Normalized(v),
+                };
+                (name, value)
+            })
+            .collect()
+    }
+}
+
+/// A single data point in a [`ScaledFrame`], with floats as multiples
+/// of `1 / scale`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ScaledDataPoint {
+    F32(i32),
+    I32(i32),
+    U32(u32),
+    Str(String),
+    Normalized(u16),
+}
+
+/// The type of a [`DataPoint`], without its value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DataType {
+    F32,
+    I32,
+    U32,
+    Str,
+    Normalized,
+}
+
+/// The channels observed in a sample of telemetry frames, for
+/// configuring dashboards automatically. Channels are sorted by key,
+/// so the serialized schema is deterministic.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Schema {
+    pub channels: BTreeMap<String, ChannelSchema>,
+}
+
+/// What was observed about a single key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ChannelSchema {
+    /// Every type the key was sent as. More than one usually points to
+    /// a bug on the device.
+    pub types: BTreeSet<DataType>,
+    /// The smallest numeric value seen, if any.
+    pub min: Option<f64>,
+    /// The largest numeric value seen, if any.
+    pub max: Option<f64>,
+}
+
+impl Schema {
+    /// Infer a schema from a sample of frames.
+    pub fn from_frames<'a>(frames: impl IntoIterator<Item = &'a TelemetryFrame>) -> Self {
+        let mut schema = Self::default();
+        for frame in frames {
+            for (key, value) in frame {
+                let channel = schema.channels.entry(key.clone()).or_default();
+                channel.types.insert(value.data_type());
+                if let Some(v) = value.as_f64() {
+                    channel.min = Some(channel.min.map_or(v, |min| min.min(v)));
+                    channel.max = Some(channel.max.map_or(v, |max| max.max(v)));
+                }
+            }
+        }
+        schema
+    }
+}
+
+/// The difference between two consecutive telemetry frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameDiff {
+    /// Keys present only in the newer frame.
+    pub added: HashMap<String, DataPoint>,
+    /// Keys present only in the older frame.
+    pub removed: HashMap<String, DataPoint>,
+    /// Keys present in both frames with differing values, as `(old,
+    /// new)`.
+    pub changed: HashMap<String, (DataPoint, DataPoint)>,
+    /// The forward increment of every changed [`DataPoint::U32`]
+    /// counter, see [`counter_delta`].
+    pub counter_deltas: HashMap<String, u32>,
+}
+
+impl FrameDiff {
+    /// Returns `true` if the frames were identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compute the difference between two telemetry frames, recording
+/// only what changed from `prev` to `next`.
+pub fn diff_frames(prev: &TelemetryFrame, next: &TelemetryFrame) -> FrameDiff {
+    let mut diff = FrameDiff::default();
+    for (key, new) in next {
+        match prev.get(key) {
+            None => {
+                diff.added.insert(key.clone(), new.clone());
+            }
+            Some(old) if old != new => {
+                if let (DataPoint::U32(old), DataPoint::U32(new)) = (old, new) {
+                    diff.counter_deltas
+                        .insert(key.clone(), counter_delta(*old, *new));
+                }
+                diff.changed.insert(key.clone(), (old.clone(), new.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, old) in prev {
+        if !next.contains_key(key) {
+            diff.removed.insert(key.clone(), old.clone());
+        }
+    }
+    diff
+}
+
+/// The increment of a wrapping `u32` counter from `prev` to `next`,
+/// assuming it wrapped at most once in between.
+pub fn counter_delta(prev: u32, next: u32) -> u32 {
+    embedded::tick_delta(prev, next)
+}
+
+/// The history of every key across a sequence of timestamped frames,
+/// for plotting.
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryHistory {
+    series: HashMap<String, Vec<(u32, DataPoint)>>,
+}
+
+impl TelemetryHistory {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append all data points of a frame received at `timestamp`.
+    pub fn push(&mut self, timestamp: u32, frame: TelemetryFrame) {
+        for (key, value) in frame {
+            self.series.entry(key).or_default().push((timestamp, value));
+        }
+    }
+
+    /// The time series of a single key, in the order frames were
+    /// pushed.
+    pub fn series(&self, key: &str) -> Option<&[(u32, DataPoint)]> {
+        self.series.get(key).map(Vec::as_slice)
+    }
+
+    /// All keys seen so far, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.series.keys().map(String::as_str)
+    }
+}
+
+/// Flags channels that have not been updated recently, so that
+/// dashboards do not show stale values as current.
+///
+/// Timestamps are ticks of a wrapping `u32` counter, see
+/// [`embedded::tick_delta`].
+#[derive(Debug, Clone, Default)]
+pub struct StallDetector {
+    max_age: HashMap<String, u32>,
+    last_seen: HashMap<String, u32>,
+}
+
+impl StallDetector {
+    /// Create a detector that watches no keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consider `key` stalled once it has not been received for more
+    /// than `max_age` ticks.
+    pub fn watch(&mut self, key: impl Into<String>, max_age: u32) {
+        self.max_age.insert(key.into(), max_age);
+    }
+
+    /// Record the keys of a frame received at `timestamp`.
+    pub fn observe(&mut self, timestamp: u32, frame: &TelemetryFrame) {
+        for key in frame.keys() {
+            if self.max_age.contains_key(key) {
+                self.last_seen.insert(key.clone(), timestamp);
+            }
+        }
+    }
+
+    /// Returns `true` if the watched `key` has not been received
+    /// within its maximum age at `now`, or was never received at all.
+    /// Keys that are not watched are never stalled.
+    pub fn is_stalled(&self, key: &str, now: u32) -> bool {
+        let Some(&max_age) = self.max_age.get(key) else {
+            return false;
+        };
+        self.last_seen
+            .get(key)
+            .map_or(true, |&seen| embedded::tick_delta(seen, now) > max_age)
+    }
+
+    /// All stalled keys at `now`, in no particular order.
+    pub fn stalled(&self, now: u32) -> impl Iterator<Item = &str> {
+        self.max_age
+            .keys()
+            .map(String::as_str)
+            .filter(move |key| self.is_stalled(key, now))
+    }
+}
+
+/// Split a frame into its `f32`, `i32`, and `u32` channels, for
+/// type-specific handling such as plotting. Normalized data points
+/// are included with the floats as fractions, and strings are left
+/// out.
+pub fn partition_by_type(
+    frame: &TelemetryFrame,
+) -> (
+    HashMap<String, f32>,
+    HashMap<String, i32>,
+    HashMap<String, u32>,
+) {
+    let mut floats = HashMap::new();
+    let mut signed = HashMap::new();
+    let mut unsigned = HashMap::new();
+    for (key, value) in frame {
+        match value {
+            DataPoint::F32(v) => {
+                floats.insert(key.clone(), *v);
+            }
+            DataPoint::I32(v) => {
+                signed.insert(key.clone(), *v);
+            }
+            DataPoint::U32(v) => {
+                unsigned.insert(key.clone(), *v);
+            }
+            DataPoint::Str(_) => {}
+            DataPoint::Normalized(v) => {
+                floats.insert(key.clone(), embedded::denormalize(*v));
+            }
+        }
+    }
+    (floats, signed, unsigned)
+}
+
+/// Return the entries of a telemetry frame sorted by key, for
+/// deterministic display.
+pub fn sorted_entries(frame: &TelemetryFrame) -> Vec<(&String, &DataPoint)> {
+    let mut entries: Vec<_> = frame.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Iterate over consecutive values of each key across a sequence of
+/// frames, yielding `(key, previous, next)` for every key present in
+/// both frames of each consecutive pair.
+///
+/// Pairs are yielded in frame order, and sorted by key within each
+/// pair of frames.
+pub fn pairwise(frames: &[TelemetryFrame]) -> impl Iterator<Item = (&str, &DataPoint, &DataPoint)> {
+    frames.windows(2).flat_map(|pair| {
+        let (prev, next) = (&pair[0], &pair[1]);
+        sorted_entries(next)
+            .into_iter()
+            .filter_map(move |(key, value)| {
+                prev.get(key)
+                    .map(|previous| (key.as_str(), previous, value))
+            })
+    })
+}
+
+/// Compute the effective telemetry rate in frames per second from the
+/// timestamps of received frames.
+///
+/// The rate is derived from the median interval between consecutive
+/// timestamps, so that a single dropout does not skew the result.
+/// Returns `0.0` if there are fewer than two timestamps or the median
+/// interval is zero.
+pub fn frame_rate(timestamps: &[u32], ticks_per_second: u32) -> f32 {
+    let mut intervals: Vec<u32> = timestamps
+        .windows(2)
+        .map(|w| w[1].wrapping_sub(w[0]))
+        .collect();
+    if intervals.is_empty() {
+        return 0.0;
+    }
+    intervals.sort_unstable();
+    let mid = intervals.len() / 2;
+    let median = if intervals.len() % 2 == 0 {
+        (intervals[mid - 1] as f32 + intervals[mid] as f32) / 2.0
+    } else {
+        intervals[mid] as f32
+    };
+    if median == 0.0 {
+        return 0.0;
+    }
+    ticks_per_second as f32 / median
 }
 
 #[cfg(test)]
@@ -23,7 +437,55 @@ mod tests {
     use super::*;
 
     use super::super::{decode, encode};
-    use crate::telemetry as embedded;
+
+    #[test]
+    fn test_interned_frame() {
+        let mut keys = embedded::KeyTable::<4>::new();
+        let mut frame = embedded::TelemetryFrame::<4>::new();
+        for (name, value) in [("altitude", 120.5f32), ("vertical_speed", -1.5)] {
+            keys.register(name).unwrap();
+            frame.insert(name, value.into()).unwrap();
+        }
+
+        let dictionary = encode(&keys, &mut [0; 64])
+            .and_then(decode::<KeyDictionary>)
+            .unwrap();
+        let mut buf = [0; 64];
+        let interned = encode(
+            &embedded::InternedFrame::new(&frame, &keys).unwrap(),
+            &mut buf,
+        )
+        .unwrap()
+        .len();
+        let plain = encode(&frame, &mut [0; 64]).unwrap().len();
+        assert!(interned < plain, "{interned} >= {plain}");
+
+        let decoded = decode::<InternedFrame>(&mut buf[..interned])
+            .and_then(|frame| dictionary.resolve(frame))
+            .unwrap();
+        assert_eq!(decoded, from_embedded(&frame));
+
+        let unknown = InternedFrame::from([(7, DataPoint::U32(1))]);
+        assert!(matches!(
+            dictionary.resolve(unknown),
+            Err(Error::UnknownKey)
+        ));
+    }
+
+    #[test]
+    fn test_ordered_frame() {
+        let mut embedded_frame = embedded::TelemetryFrame::<8>::new();
+        for (name, value) in [("zeta", 1u32), ("alpha", 2), ("mu", 3)] {
+            embedded_frame.insert(name, value.into()).unwrap();
+        }
+
+        let decoded = encode(&embedded_frame, &mut [0; 128])
+            .and_then(decode::<OrderedFrame>)
+            .unwrap();
+        let keys: Vec<_> = decoded.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["zeta", "alpha", "mu"]);
+        assert_eq!(decoded[1].1, DataPoint::U32(2));
+    }
 
     #[test]
     fn test_can_decode_embedded_version() {
@@ -39,7 +501,7 @@ mod tests {
             .unwrap();
 
         let decoded = encode(&embedded_frame, &mut [0; 128])
-            .and_then(|buf| decode::<TelemetryFrame>(buf))
+            .and_then(decode::<TelemetryFrame>)
             .unwrap();
 
         assert_eq!(decoded.len(), 3);
@@ -47,4 +509,305 @@ mod tests {
         assert_eq!(decoded.get("bar").unwrap(), &DataPoint::I32(2));
         assert_eq!(decoded.get("baz").unwrap(), &DataPoint::U32(3));
     }
+
+    #[test]
+    fn test_diff_frames() {
+        let prev = TelemetryFrame::from([
+            ("kept".to_string(), DataPoint::U32(1)),
+            ("gone".to_string(), DataPoint::I32(-1)),
+            ("moved".to_string(), DataPoint::F32(1.0)),
+        ]);
+        let next = TelemetryFrame::from([
+            ("kept".to_string(), DataPoint::U32(1)),
+            ("new".to_string(), DataPoint::I32(2)),
+            ("moved".to_string(), DataPoint::F32(2.0)),
+        ]);
+
+        let diff = diff_frames(&prev, &next);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added["new"], DataPoint::I32(2));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed["gone"], DataPoint::I32(-1));
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed["moved"],
+            (DataPoint::F32(1.0), DataPoint::F32(2.0))
+        );
+        assert!(diff_frames(&next, &next).is_empty());
+    }
+
+    #[test]
+    fn test_counter_deltas() {
+        assert_eq!(counter_delta(5, 12), 7);
+        assert_eq!(counter_delta(u32::MAX - 1, 2), 4);
+
+        let prev = TelemetryFrame::from([
+            ("frames".to_string(), DataPoint::U32(u32::MAX - 1)),
+            ("errors".to_string(), DataPoint::U32(3)),
+            ("temp".to_string(), DataPoint::F32(20.0)),
+        ]);
+        let next = TelemetryFrame::from([
+            ("frames".to_string(), DataPoint::U32(2)),
+            ("errors".to_string(), DataPoint::U32(3)),
+            ("temp".to_string(), DataPoint::F32(21.0)),
+        ]);
+        let diff = diff_frames(&prev, &next);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(
+            diff.counter_deltas,
+            HashMap::from([("frames".to_string(), 4)])
+        );
+    }
+
+    #[test]
+    fn test_stall_detector() {
+        let mut detector = StallDetector::new();
+        detector.watch("gps", 100);
+        assert!(detector.is_stalled("gps", 0));
+
+        let frame = TelemetryFrame::from([
+            ("gps".to_string(), DataPoint::U32(1)),
+            ("imu".to_string(), DataPoint::U32(1)),
+        ]);
+        detector.observe(u32::MAX - 9, &frame);
+        assert!(!detector.is_stalled("gps", 50));
+        assert!(!detector.is_stalled("gps", 90));
+        assert!(detector.is_stalled("gps", 91));
+        assert_eq!(detector.stalled(91).collect::<Vec<_>>(), ["gps"]);
+        assert!(!detector.is_stalled("imu", 1000));
+
+        detector.observe(95, &frame);
+        assert_eq!(detector.stalled(100).count(), 0);
+    }
+
+    #[test]
+    fn test_frame_rate() {
+        let timestamps = [0, 100, 200, 300, 400];
+        assert_eq!(frame_rate(&timestamps, 1000), 10.0);
+    }
+
+    #[test]
+    fn test_frame_rate_with_dropout() {
+        let timestamps = [0, 100, 200, 1200, 1300, 1400];
+        assert_eq!(frame_rate(&timestamps, 1000), 10.0);
+    }
+
+    #[test]
+    fn test_frame_rate_too_few_timestamps() {
+        assert_eq!(frame_rate(&[], 1000), 0.0);
+        assert_eq!(frame_rate(&[42], 1000), 0.0);
+    }
+
+    #[test]
+    fn test_sorted_entries() {
+        let mut frame = TelemetryFrame::new();
+        for key in ["zeta", "alpha", "mu", "beta"] {
+            frame.insert(key.to_string(), DataPoint::U32(0));
+        }
+        let keys: Vec<_> = sorted_entries(&frame)
+            .into_iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(keys, ["alpha", "beta", "mu", "zeta"]);
+    }
+
+    #[test]
+    fn test_from_embedded() {
+        let mut embedded_frame = embedded::TelemetryFrame::<4>::new();
+        embedded_frame.insert("foo", 1.0f32.into()).unwrap();
+        embedded_frame.insert("bar", (-2i32).into()).unwrap();
+        embedded_frame.insert("baz", 3u32.into()).unwrap();
+
+        let frame = from_embedded(&embedded_frame);
+
+        assert_eq!(frame.len(), 3);
+        assert_eq!(frame["foo"], DataPoint::F32(1.0));
+        assert_eq!(frame["bar"], DataPoint::I32(-2));
+        assert_eq!(frame["baz"], DataPoint::U32(3));
+    }
+
+    #[test]
+    fn test_string_data_point_round_trip() {
+        let mut embedded_frame = embedded::TelemetryFrame::<1>::new();
+        embedded_frame.insert("phase", "descent".into()).unwrap();
+
+        let decoded = encode(&embedded_frame, &mut [0; 32])
+            .and_then(decode::<TelemetryFrame>)
+            .unwrap();
+
+        assert_eq!(decoded["phase"], DataPoint::Str("descent".to_string()));
+        assert_eq!(from_embedded(&embedded_frame), decoded);
+    }
+
+    #[test]
+    fn test_normalized_data_point_round_trip() {
+        let mut embedded_frame = embedded::TelemetryFrame::<1>::new();
+        embedded_frame
+            .insert("throttle", embedded::DataPoint::normalized(0.5))
+            .unwrap();
+
+        let decoded = encode(&embedded_frame, &mut [0; 32])
+            .and_then(decode::<TelemetryFrame>)
+            .unwrap();
+
+        let fraction = decoded["throttle"].fraction().unwrap();
+        assert!((fraction - 0.5).abs() <= 0.5 / u16::MAX as f32);
+        assert_eq!(DataPoint::U32(1).fraction(), None);
+    }
+
+    #[test]
+    fn test_scaled_frame_round_trip() {
+        let mut embedded_frame = embedded::TelemetryFrame::<4>::new();
+        embedded_frame.insert("alt", 123.4567.into()).unwrap();
+        embedded_frame.insert("temp", (-0.0042).into()).unwrap();
+        embedded_frame.insert("count", 7u32.into()).unwrap();
+
+        let scaled = embedded::ScaledFrame::new(&embedded_frame, 1000);
+        let decoded = encode(&scaled, &mut [0; 64])
+            .and_then(decode::<ScaledFrame>)
+            .unwrap();
+        assert_eq!(decoded.scale, 1000);
+        assert_eq!(decoded.frame["alt"], ScaledDataPoint::F32(123457));
+
+        let frame = decoded.unscale();
+        let DataPoint::F32(alt) = frame["alt"] else {
+            panic!("not a float");
+        };
+        assert!((alt - 123.4567).abs() <= 0.0005);
+        let DataPoint::F32(temp) = frame["temp"] else {
+            panic!("not a float");
+        };
+        assert!((temp + 0.0042).abs() <= 0.0005);
+        assert_eq!(frame["count"], DataPoint::U32(7));
+    }
+
+    #[test]
+    fn test_schema_from_frames() {
+        let frames = [
+            TelemetryFrame::from([
+                ("alt".to_string(), DataPoint::F32(10.0)),
+                ("mode".to_string(), DataPoint::Str("idle".to_string())),
+                ("count".to_string(), DataPoint::U32(3)),
+            ]),
+            TelemetryFrame::from([
+                ("alt".to_string(), DataPoint::F32(-2.5)),
+                ("count".to_string(), DataPoint::I32(7)),
+            ]),
+        ];
+        let schema = Schema::from_frames(&frames);
+        assert_eq!(schema.channels.len(), 3);
+
+        let alt = &schema.channels["alt"];
+        assert_eq!(alt.types, BTreeSet::from([DataType::F32]));
+        assert_eq!((alt.min, alt.max), (Some(-2.5), Some(10.0)));
+
+        let mode = &schema.channels["mode"];
+        assert_eq!(mode.types, BTreeSet::from([DataType::Str]));
+        assert_eq!((mode.min, mode.max), (None, None));
+
+        let count = &schema.channels["count"];
+        assert_eq!(count.types, BTreeSet::from([DataType::I32, DataType::U32]));
+        assert_eq!((count.min, count.max), (Some(3.0), Some(7.0)));
+    }
+
+    #[test]
+    fn test_partition_by_type() {
+        let frame = TelemetryFrame::from([
+            ("alt".to_string(), DataPoint::F32(1.5)),
+            ("throttle".to_string(), DataPoint::Normalized(u16::MAX)),
+            ("offset".to_string(), DataPoint::I32(-3)),
+            ("count".to_string(), DataPoint::U32(7)),
+            ("mode".to_string(), DataPoint::Str("idle".to_string())),
+        ]);
+        let (floats, signed, unsigned) = partition_by_type(&frame);
+        assert_eq!(
+            floats,
+            HashMap::from([("alt".to_string(), 1.5), ("throttle".to_string(), 1.0)])
+        );
+        assert_eq!(signed, HashMap::from([("offset".to_string(), -3)]));
+        assert_eq!(unsigned, HashMap::from([("count".to_string(), 7)]));
+    }
+
+    #[test]
+    fn test_pairwise() {
+        let frames = [
+            TelemetryFrame::from([
+                ("a".to_string(), DataPoint::U32(1)),
+                ("b".to_string(), DataPoint::U32(10)),
+            ]),
+            TelemetryFrame::from([
+                ("b".to_string(), DataPoint::U32(20)),
+                ("a".to_string(), DataPoint::U32(2)),
+            ]),
+            TelemetryFrame::from([
+                ("b".to_string(), DataPoint::U32(30)),
+                ("c".to_string(), DataPoint::U32(0)),
+            ]),
+        ];
+
+        let pairs: Vec<_> = pairwise(&frames).collect();
+
+        assert_eq!(
+            pairs,
+            [
+                ("a", &DataPoint::U32(1), &DataPoint::U32(2)),
+                ("b", &DataPoint::U32(10), &DataPoint::U32(20)),
+                ("b", &DataPoint::U32(20), &DataPoint::U32(30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = TelemetryHistory::new();
+        history.push(
+            10,
+            TelemetryFrame::from([
+                ("alt".to_string(), DataPoint::F32(1.0)),
+                ("mode".to_string(), DataPoint::U32(0)),
+            ]),
+        );
+        history.push(
+            20,
+            TelemetryFrame::from([("alt".to_string(), DataPoint::F32(2.0))]),
+        );
+        history.push(
+            30,
+            TelemetryFrame::from([
+                ("alt".to_string(), DataPoint::F32(3.0)),
+                ("mode".to_string(), DataPoint::U32(1)),
+            ]),
+        );
+
+        assert_eq!(
+            history.series("alt").unwrap(),
+            [
+                (10, DataPoint::F32(1.0)),
+                (20, DataPoint::F32(2.0)),
+                (30, DataPoint::F32(3.0)),
+            ]
+        );
+        assert_eq!(
+            history.series("mode").unwrap(),
+            [(10, DataPoint::U32(0)), (30, DataPoint::U32(1))]
+        );
+        assert!(history.series("missing").is_none());
+        assert_eq!(history.keys().count(), 2);
+    }
+
+    #[test]
+    fn test_timestamped_round_trip() {
+        let mut reporter = embedded::TelemetryReporter::<2, 0, 2>::new();
+        reporter.record_at("gyro", 0.5, 1003).unwrap();
+        reporter.record_at("accel", -2i32, 1007).unwrap();
+        let embedded_frame = reporter.report_timestamped(1010);
+
+        let decoded = encode(&embedded_frame, &mut [0; 64])
+            .and_then(decode::<TimestampedFrame>)
+            .unwrap();
+
+        assert_eq!(decoded["gyro"], (1003, DataPoint::F32(0.5)));
+        assert_eq!(decoded["accel"], (1007, DataPoint::I32(-2)));
+    }
 }